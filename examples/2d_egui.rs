@@ -21,13 +21,10 @@ fn main() {
 }
 
 fn setup(mut commands: Commands) {
-    commands.spawn((Camera2dBundle::default(), DefaultBlurRegionsCamera::default()));
+    commands.spawn((Camera2dBundle::default(), BlurRegionsCamera::default()));
 }
 
-fn update(
-    mut contexts: EguiContexts,
-    mut blur_region_cameras: Query<Entity, With<bevy_blur_regions::DefaultBlurRegionsCamera>>,
-) {
+fn update(mut contexts: EguiContexts, mut blur_region_cameras: Query<Entity, With<BlurRegionsCamera>>) {
     let entity = blur_region_cameras.single_mut();
 
     let frame = egui::Frame::window(&contexts.ctx_mut().style())
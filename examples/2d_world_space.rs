@@ -0,0 +1,56 @@
+// Demonstrates how to blur behind 2D sprites with `BoundsBlurRegion` while the camera pans and zooms.
+//   cargo run --example 2d_world_space
+
+#[path = "./utils.rs"]
+mod utils;
+
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_2d))
+        .add_systems(Update, (move_camera, rotate_panel))
+        .run();
+}
+
+#[derive(Component)]
+struct Panel;
+
+fn setup(mut commands: Commands) {
+    commands.spawn((Camera2dBundle::default(), BlurRegionsCamera::default()));
+
+    commands.spawn((
+        Panel,
+        BoundsBlurRegion::default(),
+        BlurRegionSettings {
+            circle_of_confusion: 50.0,
+            ..default()
+        },
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba_u8(27, 27, 27, 100),
+                custom_size: Some(Vec2::new(300.0, 150.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..default()
+        },
+    ));
+}
+
+fn move_camera(time: Res<Time>, mut cameras: Query<(&mut Transform, &mut OrthographicProjection)>) {
+    let t = time.elapsed_seconds();
+    for (mut transform, mut projection) in &mut cameras {
+        transform.translation.x = 200.0 * (0.5 * t).sin();
+        projection.scale = 1.0 + 0.5 * (0.3 * t).sin();
+    }
+}
+
+fn rotate_panel(time: Res<Time>, mut panels: Query<&mut Transform, With<Panel>>) {
+    for mut transform in &mut panels {
+        transform.rotate_z(0.2 * time.delta_seconds());
+    }
+}
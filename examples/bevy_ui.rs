@@ -36,6 +36,10 @@ fn setup(mut commands: Commands) {
     // UI node with blur region
     commands.spawn((
         BlurRegion,
+        BlurRegionSettings {
+            tint: Color::srgba_u8(27, 27, 27, 100),
+            ..default()
+        },
         NodeBundle {
             border_color: Color::BLACK.into(),
             border_radius: BorderRadius::new(Val::ZERO, Val::Percent(5.0), Val::Percent(10.0), Val::Percent(15.0)),
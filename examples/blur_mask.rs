@@ -0,0 +1,37 @@
+// Demonstrates how to blur behind the silhouette of a mesh with `BlurMask`.
+//   cargo run --example blur_mask
+
+#[path = "./utils.rs"]
+mod utils;
+
+use bevy::prelude::*;
+use bevy::sprite::Mesh2dHandle;
+use bevy_blur_regions::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_2d))
+        .add_systems(Update, deform_mask)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.spawn((Camera2dBundle::default(), BlurRegionsCamera::default()));
+
+    // The mesh has no material, so only its silhouette is used to blur the scene behind it.
+    commands.spawn((
+        BlurMask::default(),
+        Mesh2dHandle(meshes.add(Annulus::new(100.0, 200.0))),
+        SpatialBundle::default(),
+    ));
+}
+
+fn deform_mask(time: Res<Time>, mut masks: Query<&mut Transform, With<BlurMask>>) {
+    let t = time.elapsed_seconds();
+    for mut transform in &mut masks {
+        transform.scale = Vec3::new(1.0 + 0.5 * t.sin(), 1.0 + 0.5 * (1.3 * t).cos(), 1.0);
+        transform.rotation = Quat::from_rotation_z(0.4 * t);
+    }
+}
@@ -1,6 +1,8 @@
 // The blurring algorithm has the tendancy to introduce banding.
 // This example demonstrates how to use Bevy's standard tonemapping
 // and deband dithering to improve the appearance of the blurring.
+// On cameras without HDR, grain can be used to hide banding instead,
+// see `BlurRegionSettings::grain_intensity`.
 //   cargo run --example deband_dither
 
 use bevy::core_pipeline::tonemapping::DebandDither;
@@ -22,7 +24,7 @@ fn main() {
 
 fn setup(mut commands: Commands) {
     commands.spawn((
-        DefaultBlurRegionsCamera::default(),
+        BlurRegionsCamera::default(),
         Camera3dBundle {
             // Enable HDR on the camera to enable tonemapping.
             // Fullscreen dithering runs in the tonemapping shader, which is
@@ -36,7 +38,7 @@ fn setup(mut commands: Commands) {
     ));
 }
 
-fn update(windows: Query<&Window>, mut blur_region_cameras: Query<&mut DefaultBlurRegionsCamera>) {
+fn update(windows: Query<&Window>, mut blur_region_cameras: Query<&mut BlurRegionsCamera>) {
     let Ok(window) = windows.get_single() else {
         return;
     };
@@ -22,7 +22,7 @@ fn main() {
 
 fn setup(mut commands: Commands) {
     commands.spawn((
-        DefaultBlurRegionsCamera::default(),
+        BlurRegionsCamera::default(),
         Camera3dBundle {
             transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
             ..default()
@@ -30,10 +30,7 @@ fn setup(mut commands: Commands) {
     ));
 }
 
-fn update(
-    mut contexts: EguiContexts,
-    mut blur_region_cameras: Query<Entity, With<bevy_blur_regions::DefaultBlurRegionsCamera>>,
-) {
+fn update(mut contexts: EguiContexts, mut blur_region_cameras: Query<Entity, With<BlurRegionsCamera>>) {
     let entity = blur_region_cameras.single_mut();
 
     let frame = egui::Frame::window(&contexts.ctx_mut().style())
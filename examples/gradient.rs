@@ -0,0 +1,82 @@
+// Demonstrates how to vary the strength of a blur across a blur region with `BlurRegionGradient`.
+//   cargo run --example gradient
+
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+
+#[path = "./utils.rs"]
+mod utils;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_3d))
+        .add_systems(Update, blur_spotlight)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    // 3D camera
+    commands.spawn((
+        BlurRegionsCamera::default(),
+        Camera3dBundle {
+            camera: Camera { order: 1, ..default() },
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+
+    // UI camera
+    commands.spawn(Camera2dBundle {
+        camera: Camera { order: 2, ..default() },
+        ..default()
+    });
+
+    // A navigation bar whose blur fades out towards the content below it.
+    commands.spawn((
+        BlurRegion,
+        BlurRegionSettings {
+            circle_of_confusion: 60.0,
+            gradient: Some(BlurRegionGradient::vertical(1.0, 0.0)),
+            ..default()
+        },
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(25.0),
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+fn blur_spotlight(windows: Query<&Window>, mut blur_region_cameras: Query<&mut BlurRegionsCamera>) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let Ok(mut blur_regions) = blur_region_cameras.get_single_mut() else {
+        return;
+    };
+
+    // Everything is sharp around the cursor, and increasingly blurry further away from it.
+    let screen_size = Vec2::new(
+        window.resolution.physical_width() as f32,
+        window.resolution.physical_height() as f32,
+    );
+    let cursor = window.physical_cursor_position().unwrap_or(screen_size * 0.5);
+    let top = screen_size * Vec2::new(0.0, 0.25);
+    blur_regions.blur_with(
+        Rect::from_corners(top, screen_size),
+        BlurRegionSettings {
+            circle_of_confusion: 40.0,
+            gradient: Some(BlurRegionGradient::Radial {
+                center: (cursor - top) / (screen_size - top),
+                start: 0.0,
+                end: 1.0,
+            }),
+            ..default()
+        },
+    );
+}
@@ -19,7 +19,7 @@ fn main() {
 
 fn setup(mut commands: Commands) {
     commands.spawn((
-        DefaultBlurRegionsCamera::default(),
+        BlurRegionsCamera::default(),
         Camera3dBundle {
             transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
             ..default()
@@ -27,7 +27,7 @@ fn setup(mut commands: Commands) {
     ));
 }
 
-fn update(windows: Query<&Window>, mut blur_region_cameras: Query<&mut DefaultBlurRegionsCamera>) {
+fn update(windows: Query<&Window>, mut blur_region_cameras: Query<&mut BlurRegionsCamera>) {
     let Ok(window) = windows.get_single() else {
         return;
     };
@@ -43,8 +43,13 @@ fn update(windows: Query<&Window>, mut blur_region_cameras: Query<&mut DefaultBl
         vec2(0.25, 0.5) * screen_size,
         vec2(0.3, 0.5) * screen_size,
     ));
-    blur_regions.blur(Rect::from_center_size(
-        vec2(0.75, 0.5) * screen_size,
-        vec2(0.3, 0.5) * screen_size,
-    ));
+    // Blur regions can override the settings of the camera.
+    blur_regions.blur_with(
+        Rect::from_center_size(vec2(0.75, 0.5) * screen_size, vec2(0.3, 0.5) * screen_size),
+        BlurRegionSettings {
+            circle_of_confusion: 25.0,
+            feather: 10.0,
+            ..default()
+        },
+    );
 }
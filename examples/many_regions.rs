@@ -0,0 +1,55 @@
+// Demonstrates that many small blur regions stay cheap, as each pixel only tests the blur regions
+// overlapping its tile of the screen.
+//   cargo run --example many_regions
+
+#[path = "./utils.rs"]
+mod utils;
+
+use bevy::math::vec2;
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_3d))
+        .add_systems(Update, update)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        BlurRegionsCamera::default(),
+        Camera3dBundle {
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+}
+
+fn update(time: Res<Time>, windows: Query<&Window>, mut blur_region_cameras: Query<&mut BlurRegionsCamera>) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let Ok(mut blur_regions) = blur_region_cameras.get_single_mut() else {
+        return;
+    };
+
+    let screen_size = Vec2::new(
+        window.resolution.physical_width() as f32,
+        window.resolution.physical_height() as f32,
+    );
+    let settings = BlurRegionSettings {
+        circle_of_confusion: 30.0,
+        ..default()
+    };
+    // A grid of 160 badges, bobbing up and down.
+    for y in 0..10 {
+        for x in 0..16 {
+            let offset = (time.elapsed_seconds() * 2.0 + (x + y) as f32 * 0.4).sin() * 8.0;
+            let center = vec2((x as f32 + 0.5) / 16.0, (y as f32 + 0.5) / 10.0) * screen_size + vec2(0.0, offset);
+            blur_regions.blur_shape_with(center, Circle::new(20.0), settings);
+        }
+    }
+}
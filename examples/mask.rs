@@ -0,0 +1,74 @@
+// Demonstrates how to blur behind an arbitrary shape using an image as a mask.
+//   cargo run --example mask
+
+#[path = "./utils.rs"]
+mod utils;
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::Extent3d;
+use bevy::render::render_resource::TextureDimension;
+use bevy::render::render_resource::TextureFormat;
+use bevy_blur_regions::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_3d))
+        .run();
+}
+
+fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>, windows: Query<&Window>) {
+    commands.spawn((
+        BlurRegionsCamera::default(),
+        Camera3dBundle {
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    // Usually the mask would be loaded from a file, here a star shape is drawn into an image instead.
+    let mask = images.add(star_image(256));
+    let screen_size = window.physical_size().as_vec2();
+    commands.spawn((
+        ScreenBlurRegion::new(Rect::from_center_size(
+            0.5 * screen_size,
+            Vec2::splat(0.6 * screen_size.y),
+        ))
+        .with_mask(mask),
+        BlurRegionSettings {
+            tint: Color::srgba_u8(27, 27, 27, 100),
+            ..default()
+        },
+    ));
+}
+
+fn star_image(size: u32) -> Image {
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let point = (Vec2::new(x as f32, y as f32) + 0.5) / size as f32 * 2.0 - 1.0;
+            let angle = point.y.atan2(point.x);
+            let radius = 0.65 + 0.3 * (5.0 * angle).cos();
+            let alpha = if point.length() < radius { 255 } else { 0 };
+            data.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
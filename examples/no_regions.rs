@@ -1,4 +1,5 @@
-// Demonstrates that a scene without any blur regions functions correctly.
+// Demonstrates that a scene without any blur regions functions correctly. The blur passes are
+// skipped entirely while a camera has nothing to blur.
 //   cargo run --example no_regions
 
 #[path = "./utils.rs"]
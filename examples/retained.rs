@@ -0,0 +1,63 @@
+// Demonstrates how to spawn blur regions as entities with `ScreenBlurRegion`.
+// Press space to despawn and respawn the blur region.
+//   cargo run --example retained
+
+#[path = "./utils.rs"]
+mod utils;
+
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, spawn_blur_region, utils::spawn_example_scene_3d))
+        .add_systems(Update, toggle_blur_region)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        BlurRegionsCamera::default(),
+        Camera3dBundle {
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+}
+
+fn spawn_blur_region(mut commands: Commands, windows: Query<&Window>) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    let screen_size = window.physical_size().as_vec2();
+    commands.spawn((
+        ScreenBlurRegion::new(Rect::from_center_size(0.5 * screen_size, 0.5 * screen_size))
+            .with_border_radii(Vec4::splat(30.0)),
+        BlurRegionSettings {
+            tint: Color::srgba_u8(27, 27, 27, 100),
+            ..default()
+        },
+    ));
+}
+
+fn toggle_blur_region(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    windows: Query<&Window>,
+    blur_regions: Query<Entity, With<ScreenBlurRegion>>,
+) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+
+    if blur_regions.is_empty() {
+        spawn_blur_region(commands, windows);
+    } else {
+        for entity in &blur_regions {
+            commands.entity(entity).despawn();
+        }
+    }
+}
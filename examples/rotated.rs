@@ -0,0 +1,78 @@
+// Demonstrates how blur regions follow rotated and scaled Bevy UI nodes.
+//   cargo run --example rotated
+
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+
+#[path = "./utils.rs"]
+mod utils;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_3d))
+        .add_systems(Update, animate_card)
+        .run();
+}
+
+#[derive(Component)]
+struct Card;
+
+fn setup(mut commands: Commands) {
+    // 3D camera
+    commands.spawn((
+        BlurRegionsCamera::default(),
+        Camera3dBundle {
+            camera: Camera { order: 1, ..default() },
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+
+    // UI camera
+    commands.spawn(Camera2dBundle {
+        camera: Camera { order: 2, ..default() },
+        ..default()
+    });
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Card,
+                BlurRegion,
+                BlurRegionSettings {
+                    tint: Color::srgba_u8(27, 27, 27, 100),
+                    feather: 1.0,
+                    ..default()
+                },
+                NodeBundle {
+                    border_radius: BorderRadius::all(Val::Px(20.0)),
+                    style: Style {
+                        width: Val::Px(300.0),
+                        height: Val::Px(400.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn animate_card(time: Res<Time>, mut cards: Query<&mut Transform, With<Card>>) {
+    let t = time.elapsed_seconds();
+    for mut transform in &mut cards {
+        transform.rotation = Quat::from_rotation_z(0.3 * t.sin());
+        transform.scale = Vec3::splat(1.0 + 0.2 * (0.7 * t).sin());
+    }
+}
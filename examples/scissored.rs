@@ -0,0 +1,49 @@
+// Demonstrates how to only run the blur passes over the bounds of the blur regions with
+// `BlurRenderMode::Scissored`, which is cheaper than blurring the whole screen when the blur
+// regions are small.
+//   cargo run --example scissored
+
+#[path = "./utils.rs"]
+mod utils;
+
+use bevy::math::vec2;
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_3d))
+        .add_systems(Update, update)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        BlurRegionsCamera::default().with_render_mode(BlurRenderMode::Scissored),
+        Camera3dBundle {
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+}
+
+fn update(time: Res<Time>, windows: Query<&Window>, mut blur_region_cameras: Query<&mut BlurRegionsCamera>) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let Ok(mut blur_regions) = blur_region_cameras.get_single_mut() else {
+        return;
+    };
+
+    let screen_size = Vec2::new(
+        window.resolution.physical_width() as f32,
+        window.resolution.physical_height() as f32,
+    );
+    for i in 0..5 {
+        let t = time.elapsed_seconds() + i as f32 * 1.2;
+        let center = vec2(0.5 + 0.35 * t.cos(), 0.5 + 0.35 * (1.7 * t).sin()) * screen_size;
+        blur_regions.rounded_blur(Rect::from_center_size(center, Vec2::splat(120.0)), Vec4::splat(20.0));
+    }
+}
@@ -22,7 +22,7 @@ fn main() {
 
 fn setup(mut commands: Commands) {
     commands.spawn((
-        DefaultBlurRegionsCamera::default(),
+        BlurRegionsCamera::default(),
         Camera3dBundle {
             transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
             ..default()
@@ -30,10 +30,7 @@ fn setup(mut commands: Commands) {
     ));
 }
 
-fn update(
-    mut contexts: EguiContexts,
-    mut blur_region_cameras: Query<&mut bevy_blur_regions::DefaultBlurRegionsCamera>,
-) {
+fn update(mut contexts: EguiContexts, mut blur_region_cameras: Query<&mut BlurRegionsCamera>) {
     let mut blur_regions = blur_region_cameras.single_mut();
 
     let frame = egui::Frame::window(&contexts.ctx_mut().style())
@@ -44,6 +41,10 @@ fn update(
         .shadow(egui::epaint::Shadow::NONE);
 
     let mut circle_of_confusion = blur_regions.circle_of_confusion;
+    let mut saturation = blur_regions.saturation;
+    let mut brightness = blur_regions.brightness;
+    let mut contrast = blur_regions.contrast;
+    let mut algorithm = blur_regions.algorithm;
 
     egui::Window::new("Hint")
         .frame(frame)
@@ -52,7 +53,7 @@ fn update(
         .pivot(egui::Align2::CENTER_CENTER)
         .resizable(false)
         .show_with_blur(contexts.ctx_mut(), |ui| {
-            ui.label("All blur regions on the same\ncamera share blur settings.");
+            ui.label("Blur regions use the settings of\ntheir camera unless they are\ngiven their own settings.");
         });
 
     egui::Window::new("Settings")
@@ -66,9 +67,30 @@ fn update(
             let circle_of_confusion_slider =
                 egui::Slider::new(&mut circle_of_confusion, 0.0..=300.0).text("Circle of confusion").suffix("px");
             ui.add(circle_of_confusion_slider);
+            ui.add(egui::Slider::new(&mut saturation, 0.0..=2.0).text("Saturation"));
+            ui.add(egui::Slider::new(&mut brightness, 0.0..=2.0).text("Brightness"));
+            ui.add(egui::Slider::new(&mut contrast, 0.0..=2.0).text("Contrast"));
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut algorithm, BlurAlgorithm::Gaussian, "Gaussian");
+                ui.radio_value(&mut algorithm, BlurAlgorithm::DualKawase, "Dual Kawase");
+                ui.radio_value(&mut algorithm, BlurAlgorithm::MipChain, "Mip chain");
+                ui.radio_value(&mut algorithm, BlurAlgorithm::GaussianCompute, "Gaussian (compute)");
+            });
         });
 
     if blur_regions.circle_of_confusion != circle_of_confusion {
         blur_regions.circle_of_confusion = circle_of_confusion;
     }
+    if blur_regions.saturation != saturation {
+        blur_regions.saturation = saturation;
+    }
+    if blur_regions.brightness != brightness {
+        blur_regions.brightness = brightness;
+    }
+    if blur_regions.contrast != contrast {
+        blur_regions.contrast = contrast;
+    }
+    if blur_regions.algorithm != algorithm {
+        blur_regions.algorithm = algorithm;
+    }
 }
@@ -0,0 +1,62 @@
+// Demonstrates how to blur regions shaped like `bevy_math` primitives.
+//   cargo run --example shapes
+
+#[path = "./utils.rs"]
+mod utils;
+
+use bevy::math::vec2;
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_3d))
+        .add_systems(Update, update)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        BlurRegionsCamera::default(),
+        Camera3dBundle {
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+}
+
+fn update(windows: Query<&Window>, mut blur_region_cameras: Query<&mut BlurRegionsCamera>) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let Ok(mut blur_regions) = blur_region_cameras.get_single_mut() else {
+        return;
+    };
+
+    let screen_size = Vec2::new(
+        window.resolution.physical_width() as f32,
+        window.resolution.physical_height() as f32,
+    );
+    let radius = screen_size.y * 0.15;
+
+    let shapes: [BlurRegionShape; 6] = [
+        Circle::new(radius).into(),
+        Ellipse::new(radius * 1.5, radius * 0.75).into(),
+        Capsule2d::new(radius * 0.5, radius).into(),
+        RegularPolygon::new(radius, 6).into(),
+        Rhombus::new(radius * 1.5, radius * 2.0).into(),
+        Triangle2d::new(
+            Vec2::Y * radius,
+            Vec2::new(-radius, -radius),
+            Vec2::new(radius, -radius),
+        )
+        .into(),
+    ];
+
+    for (i, shape) in shapes.into_iter().enumerate() {
+        let center = vec2(((i % 3) as f32 + 0.5) / 3.0, ((i / 3) as f32 + 0.5) / 2.0) * screen_size;
+        blur_regions.blur_shape(center, shape);
+    }
+}
@@ -0,0 +1,47 @@
+// Demonstrates how to blur everything except a region of the screen with `BlurRegionsCamera::blur_except`.
+//   cargo run --example spotlight
+
+#[path = "./utils.rs"]
+mod utils;
+
+use bevy::math::vec2;
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_3d))
+        .add_systems(Update, update)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        BlurRegionsCamera::default(),
+        Camera3dBundle {
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+}
+
+fn update(time: Res<Time>, windows: Query<&Window>, mut blur_region_cameras: Query<&mut BlurRegionsCamera>) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let Ok(mut blur_regions) = blur_region_cameras.get_single_mut() else {
+        return;
+    };
+
+    let screen_size = Vec2::new(
+        window.resolution.physical_width() as f32,
+        window.resolution.physical_height() as f32,
+    );
+    let offset = vec2(time.elapsed_seconds().cos(), time.elapsed_seconds().sin()) * 0.1;
+    blur_regions.rounded_blur_except(
+        Rect::from_center_size((vec2(0.5, 0.5) + offset) * screen_size, vec2(0.3, 0.4) * screen_size),
+        Vec4::splat(0.05 * screen_size.y),
+    );
+}
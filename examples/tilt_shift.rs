@@ -0,0 +1,61 @@
+// Demonstrates how to blur the whole screen except for a focus band with a tilt-shift.
+//   cargo run --example tilt_shift
+//
+// Use the up and down arrow keys to move the focus band, and the left and right arrow keys to
+// rotate it.
+
+#[path = "./utils.rs"]
+mod utils;
+
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_3d))
+        .add_systems(Update, move_focus_band)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        BlurRegionsCamera::default().with_tilt_shift(TiltShift {
+            position: 0.55,
+            band_height: 0.15,
+            ..default()
+        }),
+        Camera3dBundle {
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+}
+
+fn move_focus_band(
+    time: Res<Time>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut blur_region_cameras: Query<&mut BlurRegionsCamera>,
+) {
+    let delta = time.delta_seconds();
+    for mut blur_regions in &mut blur_region_cameras {
+        let Some(tilt_shift) = &mut blur_regions.tilt_shift else {
+            continue;
+        };
+
+        if keyboard.pressed(KeyCode::ArrowUp) {
+            tilt_shift.position -= 0.5 * delta;
+        }
+        if keyboard.pressed(KeyCode::ArrowDown) {
+            tilt_shift.position += 0.5 * delta;
+        }
+        if keyboard.pressed(KeyCode::ArrowLeft) {
+            tilt_shift.rotation *= Rot2::radians(delta);
+        }
+        if keyboard.pressed(KeyCode::ArrowRight) {
+            tilt_shift.rotation *= Rot2::radians(-delta);
+        }
+        tilt_shift.position = tilt_shift.position.clamp(0.0, 1.0);
+    }
+}
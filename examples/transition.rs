@@ -0,0 +1,55 @@
+// Demonstrates how to animate a fullscreen blur with `BlurTransition`.
+// Press space to toggle the blur.
+//   cargo run --example transition
+
+#[path = "./utils.rs"]
+mod utils;
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+use bevy_blur_regions::BlurTransitionFinished;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_3d))
+        .add_systems(Update, (toggle_blur, log_finished_transitions))
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        BlurRegionsCamera::default(),
+        BlurTransition::new(
+            Duration::from_secs_f32(0.5),
+            BlurTransitionState::default(),
+            BlurTransitionState {
+                circle_of_confusion: 100.0,
+                tint: Color::srgba_u8(27, 27, 27, 100),
+            },
+        ),
+        Camera3dBundle {
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+}
+
+fn toggle_blur(keys: Res<ButtonInput<KeyCode>>, mut transitions: Query<&mut BlurTransition>) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+
+    for mut transition in &mut transitions {
+        transition.reverse();
+    }
+}
+
+fn log_finished_transitions(mut finished_transitions: EventReader<BlurTransitionFinished>) {
+    for finished in finished_transitions.read() {
+        info!("Blur transition finished on {:?}", finished.entity);
+    }
+}
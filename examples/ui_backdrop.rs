@@ -0,0 +1,115 @@
+// Demonstrates how to blur the Bevy UI drawn below a blurred node, such as a HUD behind a modal dialog.
+//   cargo run --example ui_backdrop
+
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+
+#[path = "./utils.rs"]
+mod utils;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default().with_graph_placement(BlurRegionsGraphPlacement::UiBackdrop))
+        .add_systems(Startup, (setup, utils::spawn_example_scene_3d))
+        .add_systems(Update, toggle_dialog)
+        .run();
+}
+
+#[derive(Component)]
+struct Dialog;
+
+fn setup(mut commands: Commands) {
+    // The UI is split around the blur, so it must be rendered by the camera with the blur regions.
+    commands.spawn((
+        BlurRegionsCamera::default(),
+        Camera3dBundle {
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+
+    // HUD, drawn before the blur
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::SpaceBetween,
+                padding: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for text in ["Health: 100", "Press space to toggle the dialog", "Score: 4200"] {
+                parent.spawn(TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font_size: 32.0,
+                        ..default()
+                    },
+                ));
+            }
+        });
+
+    // Dialog with blur region, which blurs the HUD below it
+    commands
+        .spawn((
+            Dialog,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    BlurRegion,
+                    BlurRegionSettings {
+                        tint: Color::srgba_u8(27, 27, 27, 100),
+                        ..default()
+                    },
+                    NodeBundle {
+                        border_radius: BorderRadius::all(Val::Px(20.0)),
+                        style: Style {
+                            width: Val::Percent(60.0),
+                            height: Val::Percent(30.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "Paused",
+                        TextStyle {
+                            font_size: 64.0,
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+fn toggle_dialog(keys: Res<ButtonInput<KeyCode>>, mut dialogs: Query<&mut Visibility, With<Dialog>>) {
+    if !keys.just_pressed(KeyCode::Space) {
+        return;
+    }
+
+    for mut visibility in &mut dialogs {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
@@ -0,0 +1,44 @@
+// Demonstrates how to attach a blur region to an entity in the world with `WorldBlurRegion`.
+//   cargo run --example world_space
+
+#[path = "./utils.rs"]
+mod utils;
+
+use bevy::prelude::*;
+use bevy_blur_regions::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(BlurRegionsPlugin::default())
+        .add_systems(Startup, (setup, utils::spawn_example_scene_3d))
+        .add_systems(Update, orbit_camera)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        BlurRegionsCamera::default(),
+        Camera3dBundle {
+            transform: Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    ));
+
+    // A floating panel above the capsule, facing along the z axis.
+    commands.spawn((
+        WorldBlurRegion::new(Vec2::new(2.0, 1.0)),
+        BlurRegionSettings {
+            tint: Color::srgba_u8(27, 27, 27, 100),
+            ..default()
+        },
+        SpatialBundle::from_transform(Transform::from_xyz(0.0, 2.5, 0.0)),
+    ));
+}
+
+fn orbit_camera(time: Res<Time>, mut cameras: Query<&mut Transform, With<BlurRegionsCamera>>) {
+    for mut transform in &mut cameras {
+        transform.rotate_around(Vec3::ZERO, Quat::from_rotation_y(0.5 * time.delta_seconds()));
+        transform.look_at(Vec3::ZERO, Vec3::Y);
+    }
+}
@@ -1,26 +1,56 @@
 use bevy::prelude::*;
 use bevy::render::camera::NormalizedRenderTarget;
+use bevy::ui::DefaultUiCamera;
 use bevy::window::PrimaryWindow;
 
 use crate::BlurRegion;
+use crate::BlurRegionMask;
+use crate::BlurRegionSettings;
 use crate::BlurRegionsCamera;
+use crate::BlurRegionsSystems;
+use crate::BlurTransition;
 
-pub struct BlurRegionsBevyUiPlugin<const N: usize>;
+pub struct BlurRegionsBevyUiPlugin;
 
-impl<const N: usize> Plugin for BlurRegionsBevyUiPlugin<N> {
+impl Plugin for BlurRegionsBevyUiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Last, crate::bevy_ui::compute_blur_regions::<N>);
+        app.add_systems(
+            Last,
+            crate::bevy_ui::compute_blur_regions.in_set(BlurRegionsSystems::Compute),
+        );
     }
 }
 
-pub fn compute_blur_regions<const N: usize>(
-    nodes: Query<(&Node, &GlobalTransform, &BorderRadius, &ViewVisibility), With<BlurRegion>>,
-    mut blur_regions_cameras: Query<(&Camera, &mut BlurRegionsCamera<N>)>,
+/// Add this marker component alongside [`BlurRegion`] on a UI Node with a [`UiImage`] to use the
+/// alpha channel of the image as the shape of the blur region.
+#[derive(Component, Default, Clone, Copy)]
+pub struct UiImageBlurMask;
+
+#[allow(clippy::type_complexity)]
+pub fn compute_blur_regions(
+    nodes: Query<
+        (
+            &Node,
+            &GlobalTransform,
+            &BorderRadius,
+            &ViewVisibility,
+            Option<&BlurRegionSettings>,
+            Option<&BlurTransition>,
+            Option<&UiImage>,
+            Has<UiImageBlurMask>,
+            Option<&TargetCamera>,
+        ),
+        With<BlurRegion>,
+    >,
+    mut blur_regions_cameras: Query<(Entity, &Camera, &mut BlurRegionsCamera)>,
+    default_ui_camera: DefaultUiCamera,
     primary_window: Query<Entity, With<PrimaryWindow>>,
     ui_scale: Res<UiScale>,
     windows: Query<&Window>,
 ) {
-    for (camera, mut blur_regions) in &mut blur_regions_cameras {
+    let default_ui_camera = default_ui_camera.get();
+
+    for (camera_entity, camera, mut blur_regions) in &mut blur_regions_cameras {
         let Some(target) = camera.target.normalize(primary_window.get_single().ok()) else {
             continue;
         };
@@ -35,24 +65,48 @@ pub fn compute_blur_regions<const N: usize>(
 
         let viewport_size = window.size() / ui_scale.0;
 
-        for (node, transform, border_radius, visibility) in &nodes {
-            if visibility.get() == false {
+        for (node, transform, border_radius, visibility, settings, transition, image, masked, target_camera) in &nodes {
+            if !visibility.get() {
                 continue;
             }
 
-            let translation = transform.translation();
+            let (scale, rotation, translation) = transform.to_scale_rotation_translation();
             let region = Rect::from_center_size(
                 translation.xy() * window.scale_factor(),
-                node.size() * window.scale_factor(),
+                node.size() * scale.xy() * window.scale_factor(),
             );
+            // UI nodes are laid out with y down, so rotating a node counterclockwise around the z axis
+            // rotates it clockwise on the screen.
+            let (angle, _, _) = rotation.to_euler(EulerRot::ZYX);
             let resolved = [
                 border_radius.top_left,
                 border_radius.top_right,
                 border_radius.bottom_right,
                 border_radius.bottom_left,
             ]
-            .map(|v| v.resolve(node.size().y, viewport_size).unwrap_or(0.0) * window.scale_factor());
-            blur_regions.rounded_blur(region, bevy::prelude::Vec4::from_array(resolved));
+            .map(|v| {
+                v.resolve(node.size().y, viewport_size).unwrap_or(0.0) * scale.x.min(scale.y) * window.scale_factor()
+            });
+            let mut settings = settings.copied().unwrap_or_else(|| blur_regions.settings());
+            if let Some(transition) = transition {
+                settings = transition.apply(settings);
+            }
+            let mask = image.filter(|_| masked).map(|image| BlurRegionMask {
+                flip_x: image.flip_x,
+                flip_y: image.flip_y,
+                ..BlurRegionMask::new(image.texture.clone())
+            });
+            blur_regions.rotated_masked_blur_with(
+                region,
+                Vec4::from_array(resolved),
+                Rot2::radians(-angle),
+                mask.as_ref(),
+                settings,
+            );
+            // Only the camera that renders the node draws the UI above it after the blur.
+            if target_camera.map_or(default_ui_camera, |target| Some(target.entity())) == Some(camera_entity) {
+                blur_regions.blur_ui_layer(node.stack_index());
+            }
         }
     }
 }
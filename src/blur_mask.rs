@@ -0,0 +1,422 @@
+use bevy::asset::load_internal_asset;
+use bevy::ecs::query::QueryItem;
+use bevy::ecs::system::lifetimeless::Read;
+use bevy::prelude::*;
+use bevy::render::camera::ExtractedCamera;
+use bevy::render::mesh::GpuBufferInfo;
+use bevy::render::mesh::GpuMesh;
+use bevy::render::mesh::MeshVertexBufferLayoutRef;
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_graph::NodeRunError;
+use bevy::render::render_graph::RenderGraphApp;
+use bevy::render::render_graph::RenderGraphContext;
+use bevy::render::render_graph::RenderLabel;
+use bevy::render::render_graph::ViewNode;
+use bevy::render::render_graph::ViewNodeRunner;
+use bevy::render::render_resource::binding_types::uniform_buffer;
+use bevy::render::render_resource::BindGroup;
+use bevy::render::render_resource::BindGroupEntries;
+use bevy::render::render_resource::BindGroupLayout;
+use bevy::render::render_resource::BindGroupLayoutEntries;
+use bevy::render::render_resource::CachedRenderPipelineId;
+use bevy::render::render_resource::ColorTargetState;
+use bevy::render::render_resource::ColorWrites;
+use bevy::render::render_resource::DynamicUniformBuffer;
+use bevy::render::render_resource::Extent3d;
+use bevy::render::render_resource::FragmentState;
+use bevy::render::render_resource::LoadOp;
+use bevy::render::render_resource::MultisampleState;
+use bevy::render::render_resource::Operations;
+use bevy::render::render_resource::PipelineCache;
+use bevy::render::render_resource::PrimitiveState;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy::render::render_resource::RenderPassColorAttachment;
+use bevy::render::render_resource::RenderPassDescriptor;
+use bevy::render::render_resource::RenderPipelineDescriptor;
+use bevy::render::render_resource::ShaderStages;
+use bevy::render::render_resource::ShaderType;
+use bevy::render::render_resource::SpecializedMeshPipeline;
+use bevy::render::render_resource::SpecializedMeshPipelineError;
+use bevy::render::render_resource::SpecializedMeshPipelines;
+use bevy::render::render_resource::StoreOp;
+use bevy::render::render_resource::TextureDescriptor;
+use bevy::render::render_resource::TextureDimension;
+use bevy::render::render_resource::TextureFormat;
+use bevy::render::render_resource::TextureUsages;
+use bevy::render::render_resource::VertexState;
+use bevy::render::renderer::RenderContext;
+use bevy::render::renderer::RenderDevice;
+use bevy::render::renderer::RenderQueue;
+use bevy::render::texture::CachedTexture;
+use bevy::render::texture::TextureCache;
+use bevy::render::view::ExtractedView;
+use bevy::render::Extract;
+use bevy::render::ExtractSchedule;
+use bevy::render::Render;
+use bevy::render::RenderApp;
+use bevy::render::RenderSet;
+#[cfg(feature = "bevy_sprite")]
+use bevy::sprite::Mesh2dHandle;
+
+use crate::BlurRegionsCamera;
+use crate::BlurRegionsGraphPlacement;
+use crate::BlurRegionsLabel;
+
+const BLUR_MASK_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(98327510392874613097465102938475610293);
+
+/// Draws the silhouettes of blur masks into a mask texture for each camera, which is read by the
+/// blur regions shader.
+///
+/// Must be added after [`BlurRegionsShaderPlugin`](crate::shader::BlurRegionsShaderPlugin), as
+/// its render graph node runs before the blur regions node, which is added when the plugins finish.
+pub struct BlurMaskPlugin {
+    pub(crate) graph_placement: BlurRegionsGraphPlacement,
+}
+
+impl Plugin for BlurMaskPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, BLUR_MASK_SHADER_HANDLE, "blur_mask.wgsl", Shader::from_wgsl);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<SpecializedMeshPipelines<BlurMaskPipeline>>()
+            .init_resource::<ExtractedBlurMasks>()
+            .init_resource::<BlurMaskUniforms>()
+            .add_systems(ExtractSchedule, extract_blur_masks)
+            .add_systems(Render, prepare_blur_masks.in_set(RenderSet::PrepareResources));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        let render_device = render_app.world().resource::<RenderDevice>().clone();
+        render_app.insert_resource(BlurMaskPipeline::new(&render_device));
+
+        // The silhouettes are drawn in every render graph that the blur regions node is in.
+        for edges in self.graph_placement.edges() {
+            render_app
+                .add_render_graph_node::<ViewNodeRunner<BlurMaskNode>>(edges.graph, BlurMaskLabel)
+                .add_render_graph_edge(edges.graph, BlurMaskLabel, BlurRegionsLabel);
+        }
+    }
+}
+
+/// Add this component to an entity with a 2D or 3D mesh to blur behind the silhouette of the mesh,
+/// for shapes that can't be described by a blur region, such as a deformable mesh. 2D meshes
+/// require the `bevy_sprite` feature.
+///
+/// Silhouettes are blurred with the settings of the camera. Like blur regions, the blur is applied
+/// after the main pass, so a mesh that is drawn is blurred along with everything behind it. To
+/// blur behind a shape without drawing it, leave out its material.
+///
+/// Silhouettes aren't depth tested against the scene, so the whole silhouette is blurred even
+/// where the mesh is hidden behind other objects.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct BlurMask {
+    /// The camera that renders this blur mask, or `None` for every camera with a
+    /// [`BlurRegionsCamera`].
+    pub camera: Option<Entity>,
+}
+
+impl BlurMask {
+    pub fn with_camera(mut self, camera: Entity) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct BlurMaskLabel;
+
+struct ExtractedBlurMask {
+    mesh: AssetId<Mesh>,
+    world_from_local: Mat4,
+    camera: Option<Entity>,
+}
+
+#[derive(Resource, Default)]
+struct ExtractedBlurMasks {
+    masks: Vec<ExtractedBlurMask>,
+}
+
+#[cfg(feature = "bevy_sprite")]
+type BlurMaskMesh = AnyOf<(&'static Handle<Mesh>, &'static Mesh2dHandle)>;
+#[cfg(not(feature = "bevy_sprite"))]
+type BlurMaskMesh = &'static Handle<Mesh>;
+
+#[allow(clippy::type_complexity)]
+fn extract_blur_masks(
+    mut extracted: ResMut<ExtractedBlurMasks>,
+    masks: Extract<Query<(&BlurMask, BlurMaskMesh, &GlobalTransform, Option<&InheritedVisibility>)>>,
+) {
+    extracted.masks.clear();
+    for (mask, mesh, transform, visibility) in &masks {
+        if visibility.is_some_and(|visibility| !visibility.get()) {
+            continue;
+        }
+
+        #[cfg(feature = "bevy_sprite")]
+        let Some(mesh) = mesh.0.or(mesh.1.map(|mesh_2d| &mesh_2d.0)) else {
+            continue;
+        };
+
+        extracted.masks.push(ExtractedBlurMask {
+            mesh: mesh.id(),
+            world_from_local: transform.compute_matrix(),
+            camera: mask.camera,
+        });
+    }
+}
+
+#[derive(ShaderType)]
+struct BlurMaskUniform {
+    clip_from_local: Mat4,
+}
+
+#[derive(Resource, Default)]
+struct BlurMaskUniforms {
+    buffer: DynamicUniformBuffer<BlurMaskUniform>,
+}
+
+/// The silhouettes of blur masks to draw for a camera, and the texture to draw them into.
+#[derive(Component)]
+pub struct BlurMaskDraws {
+    pub(crate) texture: CachedTexture,
+    bind_group: BindGroup,
+    draws: Vec<BlurMaskDraw>,
+}
+
+struct BlurMaskDraw {
+    mesh: AssetId<Mesh>,
+    pipeline: CachedRenderPipelineId,
+    uniform_offset: u32,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prepare_blur_masks(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<BlurMaskPipeline>>,
+    pipeline: Res<BlurMaskPipeline>,
+    mut texture_cache: ResMut<TextureCache>,
+    mut uniforms: ResMut<BlurMaskUniforms>,
+    masks: Res<ExtractedBlurMasks>,
+    meshes: Res<RenderAssets<GpuMesh>>,
+    views: Query<(Entity, &ExtractedView, &ExtractedCamera), With<BlurRegionsCamera>>,
+) {
+    uniforms.buffer.clear();
+    if masks.masks.is_empty() {
+        return;
+    }
+
+    let mut view_draws = Vec::new();
+    for (entity, view, camera) in &views {
+        let Some(target_size) = camera.physical_target_size else {
+            continue;
+        };
+
+        let clip_from_world = view
+            .clip_from_world
+            .unwrap_or_else(|| view.clip_from_view * view.world_from_view.compute_matrix().inverse());
+
+        let mut draws = Vec::new();
+        for mask in &masks.masks {
+            if mask.camera.is_some_and(|camera| camera != entity) {
+                continue;
+            }
+
+            let Some(mesh) = meshes.get(mask.mesh) else {
+                continue;
+            };
+
+            let key = BlurMaskPipelineKey {
+                primitive_topology: mesh.primitive_topology(),
+            };
+            let pipeline = match pipelines.specialize(&pipeline_cache, &pipeline, key, &mesh.layout) {
+                Ok(pipeline) => pipeline,
+                Err(err) => {
+                    error!("Failed to specialize the blur mask pipeline: {err}");
+                    continue;
+                }
+            };
+
+            draws.push(BlurMaskDraw {
+                mesh: mask.mesh,
+                pipeline,
+                uniform_offset: uniforms.buffer.push(&BlurMaskUniform {
+                    clip_from_local: clip_from_world * mask.world_from_local,
+                }),
+            });
+        }
+
+        if draws.is_empty() {
+            continue;
+        }
+
+        let texture = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some("blur_mask_texture"),
+                size: Extent3d {
+                    width: target_size.x,
+                    height: target_size.y,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::R8Unorm,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+        view_draws.push((entity, texture, draws));
+    }
+
+    uniforms.buffer.write_buffer(&render_device, &render_queue);
+    let Some(binding) = uniforms.buffer.binding() else {
+        return;
+    };
+
+    let bind_group = render_device.create_bind_group(
+        "blur_mask_bind_group",
+        &pipeline.layout,
+        &BindGroupEntries::single(binding),
+    );
+    for (entity, texture, draws) in view_draws {
+        commands.entity(entity).insert(BlurMaskDraws {
+            texture,
+            bind_group: bind_group.clone(),
+            draws,
+        });
+    }
+}
+
+#[derive(Default)]
+pub struct BlurMaskNode;
+
+impl ViewNode for BlurMaskNode {
+    type ViewQuery = (Read<BlurMaskDraws>, Read<ExtractedView>);
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (blur_mask_draws, view): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let meshes = world.resource::<RenderAssets<GpuMesh>>();
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("blur mask pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &blur_mask_draws.texture.default_view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Default::default()),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        let viewport = view.viewport.as_vec4();
+        render_pass.set_viewport(viewport.x, viewport.y, viewport.z, viewport.w, 0.0, 1.0);
+
+        for draw in &blur_mask_draws.draws {
+            let (Some(pipeline), Some(mesh)) =
+                (pipeline_cache.get_render_pipeline(draw.pipeline), meshes.get(draw.mesh))
+            else {
+                continue;
+            };
+
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_bind_group(0, &blur_mask_draws.bind_group, &[draw.uniform_offset]);
+            render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            match &mesh.buffer_info {
+                GpuBufferInfo::Indexed {
+                    buffer,
+                    count,
+                    index_format,
+                } => {
+                    render_pass.set_index_buffer(buffer.slice(..), 0, *index_format);
+                    render_pass.draw_indexed(0..*count, 0, 0..1);
+                }
+                GpuBufferInfo::NonIndexed => render_pass.draw(0..mesh.vertex_count, 0..1),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+pub struct BlurMaskPipeline {
+    layout: BindGroupLayout,
+}
+
+impl BlurMaskPipeline {
+    fn new(render_device: &RenderDevice) -> Self {
+        let layout = render_device.create_bind_group_layout(
+            "blur_mask_bind_group_layout",
+            &BindGroupLayoutEntries::single(ShaderStages::VERTEX, uniform_buffer::<BlurMaskUniform>(true)),
+        );
+
+        Self { layout }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct BlurMaskPipelineKey {
+    primitive_topology: PrimitiveTopology,
+}
+
+impl SpecializedMeshPipeline for BlurMaskPipeline {
+    type Key = BlurMaskPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let vertex_buffer_layout = layout.0.get_layout(&[Mesh::ATTRIBUTE_POSITION.at_shader_location(0)])?;
+
+        Ok(RenderPipelineDescriptor {
+            label: Some("blur_mask_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: VertexState {
+                shader: BLUR_MASK_SHADER_HANDLE,
+                shader_defs: vec![],
+                entry_point: "vertex".into(),
+                buffers: vec![vertex_buffer_layout],
+            },
+            primitive: PrimitiveState {
+                topology: key.primitive_topology,
+                ..default()
+            },
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            fragment: Some(FragmentState {
+                shader: BLUR_MASK_SHADER_HANDLE,
+                shader_defs: vec![],
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: TextureFormat::R8Unorm,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+        })
+    }
+}
@@ -0,0 +1,17 @@
+// Draws the silhouettes of blur masks into the mask texture.
+
+@group(0) @binding(0) var<uniform> blur_mask: BlurMaskUniform;
+
+struct BlurMaskUniform {
+    clip_from_local: mat4x4<f32>,
+}
+
+@vertex
+fn vertex(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return blur_mask.clip_from_local * vec4(position, 1.0);
+}
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return vec4(1.0);
+}
@@ -0,0 +1,245 @@
+use bevy::prelude::*;
+use bevy::render::camera::ExtractedCamera;
+use bevy::render::render_resource::binding_types::texture_2d;
+use bevy::render::render_resource::binding_types::texture_storage_2d;
+use bevy::render::render_resource::BindGroup;
+use bevy::render::render_resource::BindGroupEntries;
+use bevy::render::render_resource::BindGroupLayout;
+use bevy::render::render_resource::BindGroupLayoutEntries;
+use bevy::render::render_resource::CachedComputePipelineId;
+use bevy::render::render_resource::ComputePassDescriptor;
+use bevy::render::render_resource::ComputePipelineDescriptor;
+use bevy::render::render_resource::Extent3d;
+use bevy::render::render_resource::PipelineCache;
+use bevy::render::render_resource::ShaderStages;
+use bevy::render::render_resource::SpecializedComputePipeline;
+use bevy::render::render_resource::SpecializedComputePipelines;
+use bevy::render::render_resource::StorageTextureAccess;
+use bevy::render::render_resource::TextureDescriptor;
+use bevy::render::render_resource::TextureDimension;
+use bevy::render::render_resource::TextureFormat;
+use bevy::render::render_resource::TextureSampleType;
+use bevy::render::render_resource::TextureUsages;
+use bevy::render::render_resource::TextureView;
+use bevy::render::renderer::RenderContext;
+use bevy::render::renderer::RenderDevice;
+use bevy::render::texture::CachedTexture;
+use bevy::render::texture::TextureCache;
+use bevy::render::Render;
+use bevy::render::RenderApp;
+use bevy::render::RenderSet;
+
+use crate::shader::BlurRegionsPipeline;
+use crate::shader::SHADER_HANDLE;
+use crate::BlurAlgorithm;
+use crate::BlurRegionsCamera;
+
+/// The number of pixels blurred by each workgroup of the compute passes, which must match
+/// `COMPUTE_WORKGROUP_SIZE` in shader.wgsl.
+pub(crate) const COMPUTE_WORKGROUP_SIZE: u32 = 128;
+
+/// The format of the textures written by the compute passes, which supports storage and has the
+/// range to hold HDR colors.
+const COMPUTE_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// Blurs the screen of each camera that uses [`BlurAlgorithm::GaussianCompute`] with a horizontal
+/// and a vertical compute pass, before the blur regions shader mixes the result into the screen.
+///
+/// Must be added after [`BlurRegionsShaderPlugin`](crate::shader::BlurRegionsShaderPlugin), as
+/// the compute passes share the bind group layout of its pipeline.
+pub struct BlurComputePlugin;
+
+impl Plugin for BlurComputePlugin {
+    fn build(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<SpecializedComputePipelines<BlurComputePipeline>>().add_systems(
+            Render,
+            prepare_blur_compute_textures.in_set(RenderSet::PrepareResources),
+        );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        let render_device = render_app.world().resource::<RenderDevice>().clone();
+        let regions_layout = render_app.world().resource::<BlurRegionsPipeline>().layout.clone();
+        render_app.insert_resource(BlurComputePipeline::new(&render_device, regions_layout));
+    }
+}
+
+/// The textures written by the compute passes of a camera. The horizontal pass blurs the screen
+/// into `horizontal`, and the vertical pass blurs `horizontal` into `vertical`.
+#[derive(Component)]
+pub struct BlurComputeTextures {
+    horizontal: CachedTexture,
+    pub(crate) vertical: CachedTexture,
+    horizontal_pipeline: CachedComputePipelineId,
+    vertical_pipeline: CachedComputePipelineId,
+}
+
+impl BlurComputeTextures {
+    /// Blurs the source texture with the compute passes. `regions_bind_group` is the bind group of
+    /// the blur regions of the camera, which the compute passes use to find the circle of confusion
+    /// of each pixel. Returns `false` without rendering anything if the pipelines aren't ready yet.
+    pub(crate) fn render(
+        &self,
+        render_context: &mut RenderContext,
+        regions_bind_group: &BindGroup,
+        source: &TextureView,
+        world: &World,
+    ) -> bool {
+        let compute_pipeline = world.resource::<BlurComputePipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let (Some(horizontal_pipeline), Some(vertical_pipeline)) = (
+            pipeline_cache.get_compute_pipeline(self.horizontal_pipeline),
+            pipeline_cache.get_compute_pipeline(self.vertical_pipeline),
+        ) else {
+            return false;
+        };
+
+        let horizontal_bind_group = render_context.render_device().create_bind_group(
+            "blur_compute_bind_group (horizontal pass)",
+            &compute_pipeline.layout,
+            &BindGroupEntries::sequential((source, &self.horizontal.default_view)),
+        );
+        let vertical_bind_group = render_context.render_device().create_bind_group(
+            "blur_compute_bind_group (vertical pass)",
+            &compute_pipeline.layout,
+            &BindGroupEntries::sequential((&self.horizontal.default_view, &self.vertical.default_view)),
+        );
+
+        // Each workgroup blurs a run of pixels along a single row or column.
+        let size = self.horizontal.texture.size();
+        let passes = [
+            (
+                "blur regions (horizontal compute pass)",
+                horizontal_pipeline,
+                &horizontal_bind_group,
+                (size.width.div_ceil(COMPUTE_WORKGROUP_SIZE), size.height),
+            ),
+            (
+                "blur regions (vertical compute pass)",
+                vertical_pipeline,
+                &vertical_bind_group,
+                (size.height.div_ceil(COMPUTE_WORKGROUP_SIZE), size.width),
+            ),
+        ];
+        for (label, pipeline, bind_group, (runs, lines)) in passes {
+            let mut compute_pass = render_context.command_encoder().begin_compute_pass(&ComputePassDescriptor {
+                label: Some(label),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(pipeline);
+            compute_pass.set_bind_group(0, regions_bind_group, &[]);
+            compute_pass.set_bind_group(1, bind_group, &[]);
+            compute_pass.dispatch_workgroups(runs, lines, 1);
+        }
+
+        true
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prepare_blur_compute_textures(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedComputePipelines<BlurComputePipeline>>,
+    pipeline: Res<BlurComputePipeline>,
+    regions_pipeline: Res<BlurRegionsPipeline>,
+    mut texture_cache: ResMut<TextureCache>,
+    views: Query<(Entity, &ExtractedCamera, &BlurRegionsCamera)>,
+) {
+    if !regions_pipeline.compute_supported {
+        return;
+    }
+
+    for (entity, camera, blur_regions) in &views {
+        if blur_regions.algorithm != BlurAlgorithm::GaussianCompute {
+            continue;
+        }
+
+        let Some(target_size) = camera.physical_target_size else {
+            continue;
+        };
+
+        let mut texture = |label| {
+            texture_cache.get(
+                &render_device,
+                TextureDescriptor {
+                    label: Some(label),
+                    size: Extent3d {
+                        width: target_size.x,
+                        height: target_size.y,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: COMPUTE_TEXTURE_FORMAT,
+                    usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                },
+            )
+        };
+
+        commands.entity(entity).insert(BlurComputeTextures {
+            horizontal: texture("blur_compute_horizontal_texture"),
+            vertical: texture("blur_compute_vertical_texture"),
+            horizontal_pipeline: pipelines.specialize(&pipeline_cache, &pipeline, BlurComputePass::Horizontal),
+            vertical_pipeline: pipelines.specialize(&pipeline_cache, &pipeline, BlurComputePass::Vertical),
+        });
+    }
+}
+
+#[derive(Resource)]
+pub struct BlurComputePipeline {
+    regions_layout: BindGroupLayout,
+    layout: BindGroupLayout,
+}
+
+impl BlurComputePipeline {
+    fn new(render_device: &RenderDevice, regions_layout: BindGroupLayout) -> Self {
+        let layout = render_device.create_bind_group_layout(
+            "blur_compute_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    texture_storage_2d(COMPUTE_TEXTURE_FORMAT, StorageTextureAccess::WriteOnly),
+                ),
+            ),
+        );
+
+        Self { regions_layout, layout }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub enum BlurComputePass {
+    Horizontal,
+    Vertical,
+}
+
+impl SpecializedComputePipeline for BlurComputePipeline {
+    type Key = BlurComputePass;
+
+    fn specialize(&self, key: Self::Key) -> ComputePipelineDescriptor {
+        ComputePipelineDescriptor {
+            label: Some("blur_compute_pipeline".into()),
+            layout: vec![self.regions_layout.clone(), self.layout.clone()],
+            push_constant_ranges: vec![],
+            shader: SHADER_HANDLE,
+            shader_defs: vec!["COMPUTE_GAUSSIAN".into()],
+            entry_point: match key {
+                BlurComputePass::Horizontal => "horizontal_compute".into(),
+                BlurComputePass::Vertical => "vertical_compute".into(),
+            },
+        }
+    }
+}
@@ -1,82 +1,691 @@
+use bevy::math::FloatOrd;
 use bevy::prelude::*;
 use bevy::render::extract_component::ExtractComponent;
 use bevy::render::render_resource::ShaderType;
+use bevy::render::renderer::RenderDevice;
 
-pub const DEFAULT_MAX_BLUR_REGIONS_COUNT: usize = 20;
+use crate::BlurRegionMask;
+use crate::BlurRegionsGraphPlacement;
+use crate::TiltShift;
+
+/// The max number of blur regions that can be rendered by a single camera on
+/// platforms without storage buffer support, such as WebGL2.
+pub const UNIFORM_MAX_BLUR_REGIONS_COUNT: usize = 20;
+
+/// The size of the tiles of the screen that blur regions are binned into, so that each pixel only
+/// tests the blur regions overlapping its tile.
+/// In physical pixels.
+pub(crate) const BLUR_REGION_TILE_SIZE: u32 = 64;
+
+/// Cameras with up to this many blur regions test every blur region for every pixel, which is
+/// cheaper than binning so few blur regions.
+const MAX_UNTILED_BLUR_REGIONS_COUNT: usize = 8;
 
 /// Add this marker component to a UI Node to indicate that a blur region
 /// should be created behind it.
 #[derive(Component, Default, Clone, Copy)]
 pub struct BlurRegion;
 
+/// Settings that control how an individual blur region is blurred.
+///
+/// Add this component alongside [`BlurRegion`] on a UI Node to override the
+/// settings of the camera for that node, or pass it to
+/// [`BlurRegionsCamera::blur_with`] when using the immediate mode api.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct BlurRegionSettings {
+    /// The diameter of the circle of confusion around the current pixel that is being blurred.
+    /// A larger diameter will make the image appear more blurry.
+    /// In physical pixels.
+    /// https://en.wikipedia.org/wiki/Circle_of_confusion
+    pub circle_of_confusion: f32,
+    /// The width of the band along the inside edge of the blur region over which the blur fades
+    /// out, giving the blur region soft edges. A width of around one pixel anti-aliases the edges.
+    /// In physical pixels.
+    pub feather: f32,
+    /// Blur everything except the blur region, turning the blur region into a cut-out. When a
+    /// camera has several inverted blur regions, only the area outside of all of them is blurred.
+    pub inverted: bool,
+    /// A color drawn over the blurred area, following the exact shape of the blur region.
+    /// Use a transparent color for no tint.
+    pub tint: Color,
+    /// How the tint is blended with the blurred area.
+    pub tint_blend_mode: BlurRegionBlendMode,
+    /// Multiplies the saturation of the blurred area. `0.0` is greyscale and `1.0` is unchanged.
+    pub saturation: f32,
+    /// Multiplies the brightness of the blurred area. `1.0` is unchanged.
+    pub brightness: f32,
+    /// Scales the contrast of the blurred area around middle grey. `1.0` is unchanged.
+    pub contrast: f32,
+    /// The strength of the grain drawn over the blurred area, giving it an acrylic look and hiding
+    /// banding. `0.0` disables the grain, `0.02` to `0.05` is subtle.
+    pub grain_intensity: f32,
+    /// The size of each grain.
+    /// In physical pixels.
+    pub grain_scale: f32,
+    /// Varies the strength of the blur across the blur region, or `None` for a uniform blur.
+    pub gradient: Option<BlurRegionGradient>,
+    /// Used by [`BlurRegionOverflowPolicy::Priority`] to decide which blur regions to drop
+    /// when a camera has more blur regions than it can render. Lower priorities are dropped first.
+    pub priority: i32,
+}
+
+impl Default for BlurRegionSettings {
+    fn default() -> Self {
+        BlurRegionSettings {
+            circle_of_confusion: 100.0,
+            feather: 0.0,
+            inverted: false,
+            tint: Color::NONE,
+            tint_blend_mode: BlurRegionBlendMode::default(),
+            saturation: 1.0,
+            brightness: 1.0,
+            contrast: 1.0,
+            grain_intensity: 0.0,
+            grain_scale: 1.0,
+            gradient: None,
+            priority: 0,
+        }
+    }
+}
+
+impl BlurRegionSettings {
+    /// Whether a blur region with these settings leaves the screen unchanged, as it has nothing to
+    /// blur and no tint, color adjustments or grain to draw.
+    pub(crate) fn is_noop(&self) -> bool {
+        self.circle_of_confusion <= 0.0
+            && self.tint.alpha() <= 0.0
+            && self.saturation == 1.0
+            && self.brightness == 1.0
+            && self.contrast == 1.0
+            && self.grain_intensity <= 0.0
+    }
+}
+
+/// Varies the circle of confusion across a blur region, such as for a navigation bar whose blur
+/// fades out towards the content below it.
+///
+/// `start` and `end` are multiplied with the [`BlurRegionSettings::circle_of_confusion`] of the
+/// blur region, so `0.0` is unblurred and `1.0` is fully blurred. The tint and color adjustments
+/// of the blur region aren't affected by the gradient.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlurRegionGradient {
+    /// Ramps the blur from `start` at one edge of the blur region to `end` at the opposite edge,
+    /// along `direction`. Like shapes, the direction uses a y up coordinate system, so
+    /// [`Dir2::NEG_Y`] ramps from the top of the blur region to the bottom.
+    Linear { direction: Dir2, start: f32, end: f32 },
+    /// Ramps the blur from `start` at `center` to `end` at the corner of the blur region farthest
+    /// from it. `center` is from `(0, 0)` at the top left of the blur region to `(1, 1)` at the
+    /// bottom right.
+    Radial { center: Vec2, start: f32, end: f32 },
+}
+
+impl BlurRegionGradient {
+    /// A linear gradient from the top of the blur region to the bottom.
+    pub fn vertical(top: f32, bottom: f32) -> Self {
+        BlurRegionGradient::Linear {
+            direction: Dir2::NEG_Y,
+            start: top,
+            end: bottom,
+        }
+    }
+
+    /// A linear gradient from the left of the blur region to the right.
+    pub fn horizontal(left: f32, right: f32) -> Self {
+        BlurRegionGradient::Linear {
+            direction: Dir2::X,
+            start: left,
+            end: right,
+        }
+    }
+
+    /// Returns the kind of the gradient, its parameters relative to the center of a blur region
+    /// with the given half size, and its start and end strengths.
+    fn to_shader_values(self, half_size: Vec2) -> (u32, Vec4, Vec2) {
+        match self {
+            BlurRegionGradient::Linear { direction, start, end } => {
+                // Blur regions are in screen space with y down.
+                let direction = Vec2::new(direction.x, -direction.y);
+                let extent = (direction.abs() * half_size).element_sum().max(f32::EPSILON);
+                let params = (direction / (2.0 * extent)).extend(0.5).extend(0.0);
+                (GRADIENT_LINEAR, params, Vec2::new(start, end))
+            }
+            BlurRegionGradient::Radial { center, start, end } => {
+                let center = (center - 0.5) * 2.0 * half_size;
+                let radius = (half_size + center.abs()).length().max(f32::EPSILON);
+                let params = Vec4::new(center.x, center.y, radius.recip(), 0.0);
+                (GRADIENT_RADIAL, params, Vec2::new(start, end))
+            }
+        }
+    }
+}
+
+// Must be kept in sync with the gradient constants in shader.wgsl
+const GRADIENT_NONE: u32 = 0;
+const GRADIENT_LINEAR: u32 = 1;
+const GRADIENT_RADIAL: u32 = 2;
+
+/// How the tint of a blur region is blended with the blurred area. The alpha of the tint controls
+/// the strength of the blend.
+///
+/// Blending happens in linear color space, before tonemapping.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlurRegionBlendMode {
+    /// Draw the tint over the blurred area.
+    #[default]
+    Alpha,
+    /// Multiply the blurred area by the tint, darkening it.
+    Multiply,
+    /// The inverse of multiplying the inverse colors, lightening the blurred area.
+    Screen,
+    /// Multiply dark areas and screen light areas, increasing contrast.
+    Overlay,
+}
+
+impl BlurRegionBlendMode {
+    // Must be kept in sync with the blend mode constants in shader.wgsl
+    fn to_shader_value(self) -> u32 {
+        match self {
+            BlurRegionBlendMode::Alpha => 0,
+            BlurRegionBlendMode::Multiply => 1,
+            BlurRegionBlendMode::Screen => 2,
+            BlurRegionBlendMode::Overlay => 3,
+        }
+    }
+}
+
+/// The shape of a blur region created with [`BlurRegionsCamera::blur_shape`].
+///
+/// Shapes are oriented the same way as the `bevy_math` primitives they are made from, with the
+/// y axis pointing up the screen, and are measured in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlurRegionShape {
+    Rectangle(Rectangle),
+    Circle(Circle),
+    Ellipse(Ellipse),
+    Capsule(Capsule2d),
+    RegularPolygon(RegularPolygon),
+    Rhombus(Rhombus),
+    Triangle(Triangle2d),
+}
+
+impl From<Rectangle> for BlurRegionShape {
+    fn from(rectangle: Rectangle) -> Self {
+        BlurRegionShape::Rectangle(rectangle)
+    }
+}
+
+impl From<Circle> for BlurRegionShape {
+    fn from(circle: Circle) -> Self {
+        BlurRegionShape::Circle(circle)
+    }
+}
+
+impl From<Ellipse> for BlurRegionShape {
+    fn from(ellipse: Ellipse) -> Self {
+        BlurRegionShape::Ellipse(ellipse)
+    }
+}
+
+impl From<Capsule2d> for BlurRegionShape {
+    fn from(capsule: Capsule2d) -> Self {
+        BlurRegionShape::Capsule(capsule)
+    }
+}
+
+impl From<RegularPolygon> for BlurRegionShape {
+    fn from(polygon: RegularPolygon) -> Self {
+        BlurRegionShape::RegularPolygon(polygon)
+    }
+}
+
+impl From<Rhombus> for BlurRegionShape {
+    fn from(rhombus: Rhombus) -> Self {
+        BlurRegionShape::Rhombus(rhombus)
+    }
+}
+
+impl From<Triangle2d> for BlurRegionShape {
+    fn from(triangle: Triangle2d) -> Self {
+        BlurRegionShape::Triangle(triangle)
+    }
+}
+
+// Must be kept in sync with the shape constants in shader.wgsl
+const SHAPE_ROUNDED_BOX: u32 = 0;
+const SHAPE_CIRCLE: u32 = 1;
+const SHAPE_ELLIPSE: u32 = 2;
+const SHAPE_CAPSULE: u32 = 3;
+const SHAPE_REGULAR_POLYGON: u32 = 4;
+const SHAPE_RHOMBUS: u32 = 5;
+const SHAPE_TRIANGLE: u32 = 6;
+
+/// Decides which blur regions are kept when a camera has more blur regions than its
+/// [`BlurRegionsCamera::max_regions`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlurRegionOverflowPolicy {
+    /// Drop the blur regions that were added last.
+    #[default]
+    DropNewest,
+    /// Drop the blur regions with the smallest area.
+    DropSmallest,
+    /// Merge the blur regions that don't fit into a single blur region covering all of them.
+    MergeIntoBoundingBox,
+    /// Drop the blur regions with the lowest [`BlurRegionSettings::priority`], newest first.
+    Priority,
+}
+
+/// How a camera blurs its blur regions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BlurAlgorithm {
+    /// A separable Gaussian blur at full resolution. The highest quality, but the number of
+    /// samples per pixel grows with the circle of confusion, so large blurs are expensive.
+    #[default]
+    Gaussian,
+    /// The same separable Gaussian blur, computed by compute passes that share the pixels around
+    /// each run of pixels through workgroup memory. Falls back to [`BlurAlgorithm::Gaussian`] on
+    /// platforms without compute shader support, such as WebGL2.
+    GaussianCompute,
+    /// Repeatedly halves the resolution of the screen with the downsampling filter of the Dual
+    /// Kawase blur, and then doubles it back with its upsampling filter, stopping the upsampling
+    /// of each pixel at the level matching its circle of confusion. Large blurs cost about the
+    /// same as small ones, at the cost of some accuracy.
+    DualKawase,
+    /// Like [`BlurAlgorithm::DualKawase`], but downsamples with a wider 13 tap filter and upsamples
+    /// with a 3x3 tent filter, as in the mip chain bloom of Call of Duty: Advanced Warfare, which
+    /// is smoother and more stable when the scene moves, at a slightly higher cost.
+    MipChain,
+}
+
+/// Which parts of the screen a camera runs its blur passes over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlurRenderMode {
+    /// Every pass runs over the whole screen.
+    #[default]
+    Fullscreen,
+    /// Every pass copies the screen once, and then only runs over the bounds of each blur region,
+    /// so the cost scales with the blurred area rather than the size of the screen. Best for a few
+    /// small blur regions.
+    ///
+    /// Inverted blur regions, the tilt-shift and the silhouettes of blur masks can cover the whole
+    /// screen, so while a camera has any of them, it falls back to [`BlurRenderMode::Fullscreen`].
+    Scissored,
+}
+
+/// Sent when a camera has more blur regions than it can render and its
+/// [`BlurRegionOverflowPolicy`] had to be applied.
+#[derive(Event, Debug, Clone)]
+pub struct BlurRegionsOverflow {
+    /// The camera whose blur regions overflowed.
+    pub camera: Entity,
+    /// The number of blur regions over the limit of the camera, which were dropped or merged into
+    /// another blur region.
+    pub count: usize,
+}
+
+/// System sets for the systems that compute blur regions, in the [`Last`] schedule.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BlurRegionsSystems {
+    /// Blur regions are added to cameras from Bevy UI, egui and other sources.
+    Compute,
+    /// Cameras with too many blur regions apply their [`BlurRegionOverflowPolicy`].
+    Overflow,
+}
+
 /// The final computed values of the blur region, in physical pixels.
+///
+/// The min and max values are the bounding box of the blur region, `shape_params` are relative to
+/// the center of the bounding box, in the local frame of the blur region. `rotation` is the cosine
+/// and sine of the clockwise rotation of the blur region on the screen. `mask_layer` is the layer
+/// of the mask texture used by the blur region, or -1 for no mask. `gradient` holds the parameters
+/// of the gradient in the local frame of the blur region, and `gradient_strength` the strength of
+/// the blur at the start and end of the gradient.
 #[derive(Default, Debug, Clone, ShaderType)]
-struct ComputedBlurRegion {
+pub(crate) struct ComputedBlurRegion {
     min_x: f32,
     max_x: f32,
     min_y: f32,
     max_y: f32,
+    rotation: Vec2,
+    gradient_strength: Vec2,
     border_radii: Vec4,
+    shape_params: [Vec4; 2],
+    tint: Vec4,
+    mask_uv: Vec4,
+    gradient: Vec4,
+    tint_blend_mode: u32,
+    shape: u32,
+    circle_of_confusion: f32,
+    feather: f32,
+    inverted: u32,
+    mask_layer: i32,
+    saturation: f32,
+    brightness: f32,
+    contrast: f32,
+    grain_intensity: f32,
+    grain_scale: f32,
+    gradient_kind: u32,
 }
 
 impl ComputedBlurRegion {
-    const OFFSCREEN: ComputedBlurRegion = ComputedBlurRegion {
+    pub(crate) const OFFSCREEN: ComputedBlurRegion = ComputedBlurRegion {
         min_x: -1.0,
         max_x: -1.0,
         min_y: -1.0,
         max_y: -1.0,
+        rotation: Vec2::X,
+        gradient_strength: Vec2::ONE,
         border_radii: Vec4::ZERO,
+        shape_params: [Vec4::ZERO; 2],
+        tint: Vec4::ZERO,
+        mask_uv: Vec4::ZERO,
+        gradient: Vec4::ZERO,
+        tint_blend_mode: 0,
+        shape: SHAPE_ROUNDED_BOX,
+        circle_of_confusion: 0.0,
+        feather: 0.0,
+        inverted: 0,
+        mask_layer: -1,
+        saturation: 1.0,
+        brightness: 1.0,
+        contrast: 1.0,
+        grain_intensity: 0.0,
+        grain_scale: 1.0,
+        gradient_kind: GRADIENT_NONE,
     };
+
+    fn rounded_box(rect: Rect, border_radii: Vec4, settings: &BlurRegionSettings) -> Self {
+        let (gradient_kind, gradient, gradient_strength) =
+            settings.gradient.map_or((GRADIENT_NONE, Vec4::ZERO, Vec2::ONE), |gradient| {
+                gradient.to_shader_values(rect.half_size())
+            });
+
+        ComputedBlurRegion {
+            min_x: rect.min.x,
+            max_x: rect.max.x,
+            min_y: rect.min.y,
+            max_y: rect.max.y,
+            rotation: Vec2::X,
+            gradient_strength,
+            border_radii,
+            shape_params: [rect.half_size().extend(0.0).extend(0.0), Vec4::ZERO],
+            tint: settings.tint.to_linear().to_vec4(),
+            mask_uv: Vec4::ZERO,
+            gradient,
+            tint_blend_mode: settings.tint_blend_mode.to_shader_value(),
+            shape: SHAPE_ROUNDED_BOX,
+            circle_of_confusion: settings.circle_of_confusion,
+            feather: settings.feather,
+            inverted: settings.inverted as u32,
+            mask_layer: -1,
+            saturation: settings.saturation,
+            brightness: settings.brightness,
+            contrast: settings.contrast,
+            grain_intensity: settings.grain_intensity,
+            grain_scale: settings.grain_scale,
+            gradient_kind,
+        }
+    }
+
+    fn shape(center: Vec2, shape: BlurRegionShape, settings: &BlurRegionSettings) -> Self {
+        let (half_size, params, kind) = match shape {
+            BlurRegionShape::Rectangle(rectangle) => (
+                rectangle.half_size,
+                rectangle.half_size.extend(0.0).extend(0.0),
+                SHAPE_ROUNDED_BOX,
+            ),
+            BlurRegionShape::Circle(circle) => (
+                Vec2::splat(circle.radius),
+                Vec4::new(circle.radius, 0.0, 0.0, 0.0),
+                SHAPE_CIRCLE,
+            ),
+            BlurRegionShape::Ellipse(ellipse) => (
+                ellipse.half_size,
+                ellipse.half_size.extend(0.0).extend(0.0),
+                SHAPE_ELLIPSE,
+            ),
+            BlurRegionShape::Capsule(capsule) => (
+                Vec2::new(capsule.radius, capsule.half_length + capsule.radius),
+                Vec4::new(capsule.radius, capsule.half_length, 0.0, 0.0),
+                SHAPE_CAPSULE,
+            ),
+            // The bounding box of the circumcircle is used so that the polygon is centered in it.
+            BlurRegionShape::RegularPolygon(polygon) => (
+                Vec2::splat(polygon.circumcircle.radius),
+                Vec4::new(polygon.circumcircle.radius, polygon.sides as f32, 0.0, 0.0),
+                SHAPE_REGULAR_POLYGON,
+            ),
+            BlurRegionShape::Rhombus(rhombus) => (
+                rhombus.half_diagonals,
+                rhombus.half_diagonals.extend(0.0).extend(0.0),
+                SHAPE_RHOMBUS,
+            ),
+            BlurRegionShape::Triangle(triangle) => return ComputedBlurRegion::triangle(center, triangle, settings),
+        };
+
+        ComputedBlurRegion {
+            shape_params: [params, Vec4::ZERO],
+            ..ComputedBlurRegion::bounded(center, half_size, kind, settings)
+        }
+    }
+
+    fn triangle(center: Vec2, triangle: Triangle2d, settings: &BlurRegionSettings) -> Self {
+        // Shapes use a y up coordinate system, while blur regions are in screen space with y down.
+        let [a, b, c] = triangle.vertices.map(|vertex| Vec2::new(vertex.x, -vertex.y));
+        let min = a.min(b).min(c);
+        let max = a.max(b).max(c);
+        let offset = (min + max) * 0.5;
+        let [a, b, c] = [a, b, c].map(|vertex| vertex - offset);
+
+        ComputedBlurRegion {
+            shape_params: [Vec4::new(a.x, a.y, b.x, b.y), Vec4::new(c.x, c.y, 0.0, 0.0)],
+            ..ComputedBlurRegion::bounded(center + offset, (max - min) * 0.5, SHAPE_TRIANGLE, settings)
+        }
+    }
+
+    fn bounded(center: Vec2, half_size: Vec2, shape: u32, settings: &BlurRegionSettings) -> Self {
+        ComputedBlurRegion {
+            shape,
+            ..ComputedBlurRegion::rounded_box(Rect::from_center_half_size(center, half_size), Vec4::ZERO, settings)
+        }
+    }
+
+    /// Rotates an unrotated blur region around `origin`, growing its bounding box to fit.
+    fn rotated(self, origin: Vec2, rotation: Rot2) -> Self {
+        // Shapes and rotations use a y up coordinate system, while blur regions are in screen
+        // space with y down, so a counterclockwise rotation becomes a clockwise one.
+        let rotation = rotation.inverse();
+        let center = Vec2::new(self.max_x + self.min_x, self.max_y + self.min_y) * 0.5;
+        let half_size = Vec2::new(self.max_x - self.min_x, self.max_y - self.min_y) * 0.5;
+        let center = origin + rotation * (center - origin);
+        let half_size = Vec2::new(
+            (rotation.cos * half_size.x).abs() + (rotation.sin * half_size.y).abs(),
+            (rotation.sin * half_size.x).abs() + (rotation.cos * half_size.y).abs(),
+        );
+
+        ComputedBlurRegion {
+            min_x: center.x - half_size.x,
+            max_x: center.x + half_size.x,
+            min_y: center.y - half_size.y,
+            max_y: center.y + half_size.y,
+            rotation: Vec2::new(rotation.cos, rotation.sin),
+            ..self
+        }
+    }
+
+    fn max_circle_of_confusion(&self) -> f32 {
+        if self.gradient_kind == GRADIENT_NONE {
+            return self.circle_of_confusion;
+        }
+        self.circle_of_confusion * self.gradient_strength.max_element().max(0.0)
+    }
+
+    fn area(&self) -> f32 {
+        (self.max_x - self.min_x) * (self.max_y - self.min_y)
+    }
 }
 
-pub type DefaultBlurRegionsCamera = BlurRegionsCamera<DEFAULT_MAX_BLUR_REGIONS_COUNT>;
+#[derive(Debug, Clone)]
+struct BlurRegionEntry {
+    region: ComputedBlurRegion,
+    priority: i32,
+    mask: Option<AssetId<Image>>,
+}
+
+#[deprecated(note = "`BlurRegionsCamera` no longer has a max blur region count, use it directly instead")]
+pub type DefaultBlurRegionsCamera = BlurRegionsCamera;
 
 /// Indicates that this camera should render blur regions, as well as providing
-/// settings for the blurring.
-#[derive(Component, Debug, Clone, ExtractComponent, ShaderType)]
-pub struct BlurRegionsCamera<const N: usize> {
-    /// The diameter of the circle of confusion around the current pixel that is being blurred.
-    /// A larger diameter will make the image appear more blurry.
+/// the default settings for blur regions that don't specify their own.
+#[derive(Component, Debug, Clone, ExtractComponent)]
+pub struct BlurRegionsCamera {
+    /// The circle of confusion used by blur regions that don't specify their own
+    /// [`BlurRegionSettings`].
     /// In physical pixels.
-    /// https://en.wikipedia.org/wiki/Circle_of_confusion
     pub circle_of_confusion: f32,
-    padding_8: u32,
-    padding_16: u32,
-    current_regions_count: u32,
-    regions: [ComputedBlurRegion; N],
+    /// The saturation used by blur regions that don't specify their own [`BlurRegionSettings`].
+    pub saturation: f32,
+    /// The brightness used by blur regions that don't specify their own [`BlurRegionSettings`].
+    pub brightness: f32,
+    /// The contrast used by blur regions that don't specify their own [`BlurRegionSettings`].
+    pub contrast: f32,
+    /// The max number of blur regions this camera will render, or `None` for no limit.
+    /// On platforms without storage buffer support, such as WebGL2, the limit is always
+    /// at most 20.
+    pub max_regions: Option<usize>,
+    /// Decides which blur regions are kept once there are more than `max_regions`.
+    pub overflow_policy: BlurRegionOverflowPolicy,
+    /// A fullscreen tilt-shift blur, or `None` to only blur blur regions.
+    pub tilt_shift: Option<TiltShift>,
+    /// How blur regions are blurred.
+    pub algorithm: BlurAlgorithm,
+    /// Which parts of the screen the blur passes run over.
+    pub render_mode: BlurRenderMode,
+    regions: Vec<BlurRegionEntry>,
+    /// The stack index of the lowest blurred Bevy UI node, which the UI is split at with
+    /// [`BlurRegionsGraphPlacement::UiBackdrop`].
+    #[cfg(feature = "bevy_ui")]
+    ui_layer: Option<u32>,
 }
 
-impl Default for BlurRegionsCamera<DEFAULT_MAX_BLUR_REGIONS_COUNT> {
+impl Default for BlurRegionsCamera {
     fn default() -> Self {
         BlurRegionsCamera {
             circle_of_confusion: 100.0,
-            padding_8: 0,
-            padding_16: 0,
-            current_regions_count: 0,
-            regions: std::array::from_fn(|_| ComputedBlurRegion::OFFSCREEN),
+            saturation: 1.0,
+            brightness: 1.0,
+            contrast: 1.0,
+            max_regions: None,
+            overflow_policy: BlurRegionOverflowPolicy::default(),
+            tilt_shift: None,
+            algorithm: BlurAlgorithm::default(),
+            render_mode: BlurRenderMode::default(),
+            regions: Vec::new(),
+            #[cfg(feature = "bevy_ui")]
+            ui_layer: None,
         }
     }
 }
 
-impl<const N: usize> BlurRegionsCamera<N> {
+impl BlurRegionsCamera {
+    pub fn with_tilt_shift(mut self, tilt_shift: TiltShift) -> Self {
+        self.tilt_shift = Some(tilt_shift);
+        self
+    }
+
+    pub fn with_algorithm(mut self, algorithm: BlurAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn with_render_mode(mut self, render_mode: BlurRenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+
     pub fn blur(&mut self, rect: Rect) {
         self.rounded_blur(rect, Vec4::ZERO);
     }
 
     pub fn rounded_blur(&mut self, rect: Rect, border_radii: Vec4) {
-        if self.current_regions_count == N as u32 {
-            warn!("Blur region ignored as the max blur region count has already been reached");
-            return;
-        }
+        self.rounded_blur_with(rect, border_radii, self.settings());
+    }
 
-        self.regions[self.current_regions_count as usize] = ComputedBlurRegion {
-            min_x: rect.min.x,
-            max_x: rect.max.x,
-            min_y: rect.min.y,
-            max_y: rect.max.y,
-            border_radii,
+    pub fn blur_with(&mut self, rect: Rect, settings: BlurRegionSettings) {
+        self.rounded_blur_with(rect, Vec4::ZERO, settings);
+    }
+
+    pub fn rounded_blur_with(&mut self, rect: Rect, border_radii: Vec4, settings: BlurRegionSettings) {
+        self.push(
+            ComputedBlurRegion::rounded_box(rect, border_radii, &settings),
+            None,
+            &settings,
+        );
+    }
+
+    /// Blurs everything except the given rect.
+    pub fn blur_except(&mut self, rect: Rect) {
+        self.rounded_blur_except(rect, Vec4::ZERO);
+    }
+
+    /// Blurs everything except the given rect, with rounded corners.
+    pub fn rounded_blur_except(&mut self, rect: Rect, border_radii: Vec4) {
+        let settings = BlurRegionSettings {
+            inverted: true,
+            ..self.settings()
         };
-        self.current_regions_count += 1;
+        self.rounded_blur_with(rect, border_radii, settings);
+    }
+
+    /// Blurs the given shape, positioned so that the origin of the shape is at `center`.
+    pub fn blur_shape(&mut self, center: Vec2, shape: impl Into<BlurRegionShape>) {
+        self.blur_shape_with(center, shape, self.settings());
+    }
+
+    pub fn blur_shape_with(&mut self, center: Vec2, shape: impl Into<BlurRegionShape>, settings: BlurRegionSettings) {
+        self.push(
+            ComputedBlurRegion::shape(center, shape.into(), &settings),
+            None,
+            &settings,
+        );
+    }
+
+    /// Blurs the given rect, with rounded corners, rotated counterclockwise around its center.
+    pub fn rotated_blur_with(&mut self, rect: Rect, border_radii: Vec4, rotation: Rot2, settings: BlurRegionSettings) {
+        self.rotated_masked_blur_with(rect, border_radii, rotation, None, settings);
+    }
+
+    /// Blurs the given rect where the alpha channel of the mask is opaque.
+    pub fn masked_blur_with(&mut self, rect: Rect, mask: &BlurRegionMask, settings: BlurRegionSettings) {
+        self.rotated_masked_blur_with(rect, Vec4::ZERO, Rot2::IDENTITY, Some(mask), settings);
+    }
+
+    pub(crate) fn rotated_masked_blur_with(
+        &mut self,
+        rect: Rect,
+        border_radii: Vec4,
+        rotation: Rot2,
+        mask: Option<&BlurRegionMask>,
+        settings: BlurRegionSettings,
+    ) {
+        let mut region =
+            ComputedBlurRegion::rounded_box(rect, border_radii, &settings).rotated(rect.center(), rotation);
+        if let Some(mask) = mask {
+            region.mask_uv = mask.uv();
+        }
+        self.push(region, mask, &settings);
+    }
+
+    /// Blurs the given shape, positioned so that the origin of the shape is at `center`, rotated
+    /// counterclockwise around its origin.
+    pub fn rotated_blur_shape_with(
+        &mut self,
+        center: Vec2,
+        shape: impl Into<BlurRegionShape>,
+        rotation: Rot2,
+        settings: BlurRegionSettings,
+    ) {
+        let region = ComputedBlurRegion::shape(center, shape.into(), &settings).rotated(center, rotation);
+        self.push(region, None, &settings);
     }
 
     pub fn blur_all(&mut self, rects: &[Rect]) {
@@ -91,33 +700,631 @@ impl<const N: usize> BlurRegionsCamera<N> {
         }
     }
 
+    /// The settings used by blur regions that don't specify their own.
+    pub fn settings(&self) -> BlurRegionSettings {
+        BlurRegionSettings {
+            circle_of_confusion: self.circle_of_confusion,
+            saturation: self.saturation,
+            brightness: self.brightness,
+            contrast: self.contrast,
+            ..default()
+        }
+    }
+
+    /// An offscreen blur region with the settings of the camera, used to blur the silhouettes of
+    /// blur masks and the tilt-shift.
+    pub(crate) fn default_region(&self) -> ComputedBlurRegion {
+        ComputedBlurRegion::rounded_box(
+            Rect::from_center_size(Vec2::splat(-1.0), Vec2::ZERO),
+            Vec4::ZERO,
+            &self.settings(),
+        )
+    }
+
+    /// Records that the Bevy UI node with the given stack index is blurred, so that with
+    /// [`BlurRegionsGraphPlacement::UiBackdrop`] the UI from that node up is drawn after the blur.
+    #[cfg(feature = "bevy_ui")]
+    pub(crate) fn blur_ui_layer(&mut self, stack_index: u32) {
+        self.ui_layer = Some(self.ui_layer.map_or(stack_index, |layer| layer.min(stack_index)));
+    }
+
+    #[cfg(feature = "bevy_ui")]
+    pub(crate) fn ui_layer(&self) -> Option<u32> {
+        self.ui_layer
+    }
+
+    pub(crate) fn regions(&self) -> impl ExactSizeIterator<Item = &ComputedBlurRegion> {
+        self.regions.iter().map(|entry| &entry.region)
+    }
+
+    /// Whether the camera has anything to blur, other than the silhouettes of blur masks which are
+    /// only known to the render world.
+    pub(crate) fn is_active(&self) -> bool {
+        !self.regions.is_empty() || self.tilt_shift.is_some_and(|tilt_shift| tilt_shift.circle_of_confusion > 0.0)
+    }
+
+    /// The largest circle of confusion that any pixel may be blurred with, including the
+    /// silhouettes of blur masks and the tilt-shift.
+    pub(crate) fn max_circle_of_confusion(&self) -> f32 {
+        let tilt_shift = self.tilt_shift.map_or(0.0, |tilt_shift| tilt_shift.circle_of_confusion);
+        self.regions()
+            .map(ComputedBlurRegion::max_circle_of_confusion)
+            .fold(self.circle_of_confusion.max(tilt_shift), f32::max)
+    }
+
+    /// The bounds of the blur regions, clamped to a render target of the given size, or `None` if
+    /// the camera blurs areas that aren't bounded by its blur regions.
+    ///
+    /// Pixels outside of the bounds of every blur region are never blurred, so the bounds don't need
+    /// to be expanded by the size of the blur.
+    pub(crate) fn scissor_rects(&self, target_size: UVec2) -> Option<Vec<URect>> {
+        if self.tilt_shift.is_some_and(|tilt_shift| tilt_shift.circle_of_confusion > 0.0) {
+            return None;
+        }
+
+        let bounds = Rect::from_corners(Vec2::ZERO, target_size.as_vec2());
+        let mut rects = Vec::with_capacity(self.regions.len());
+        for region in self.regions() {
+            if region.inverted != 0 {
+                return None;
+            }
+
+            let rect = Rect::new(region.min_x, region.min_y, region.max_x, region.max_y).intersect(bounds);
+            if !rect.is_empty() {
+                rects.push(URect::from_corners(
+                    rect.min.floor().as_uvec2(),
+                    rect.max.ceil().as_uvec2(),
+                ));
+            }
+        }
+        Some(rects)
+    }
+
+    /// Bins the blur regions into tiles of [`BLUR_REGION_TILE_SIZE`] covering a render target of the
+    /// given size, returning the number of columns and rows of tiles, or zero if the blur regions
+    /// aren't binned.
+    ///
+    /// `data` starts with the offset into `data` and the number of blur regions of each tile, in
+    /// row-major order, followed by the indices of the blur regions of each tile in ascending
+    /// order. Inverted blur regions affect everything outside of them, so they are in every tile.
+    pub(crate) fn bin_regions(&self, target_size: UVec2, data: &mut Vec<u32>) -> UVec2 {
+        data.clear();
+        if self.regions.len() <= MAX_UNTILED_BLUR_REGIONS_COUNT || target_size.min_element() == 0 {
+            return UVec2::ZERO;
+        }
+
+        let grid = (target_size + (BLUR_REGION_TILE_SIZE - 1)) / BLUR_REGION_TILE_SIZE;
+        let mut tiles = vec![Vec::new(); (grid.x * grid.y) as usize];
+        for (index, region) in self.regions().enumerate() {
+            if region.inverted != 0 {
+                for tile in &mut tiles {
+                    tile.push(index as u32);
+                }
+                continue;
+            }
+
+            let min = Vec2::new(region.min_x, region.min_y);
+            let max = Vec2::new(region.max_x, region.max_y);
+            if max.cmplt(Vec2::ZERO).any() || min.cmpge(target_size.as_vec2()).any() {
+                continue;
+            }
+
+            let tile_size = BLUR_REGION_TILE_SIZE as f32;
+            let min = (min.max(Vec2::ZERO) / tile_size).as_uvec2().min(grid - 1);
+            let max = (max / tile_size).as_uvec2().min(grid - 1);
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    tiles[(y * grid.x + x) as usize].push(index as u32);
+                }
+            }
+        }
+
+        data.resize(tiles.len() * 2, 0);
+        for (index, tile) in tiles.iter().enumerate() {
+            data[index * 2] = data.len() as u32;
+            data[index * 2 + 1] = tile.len() as u32;
+            data.extend_from_slice(tile);
+        }
+        grid
+    }
+
+    pub(crate) fn masks(&self) -> impl Iterator<Item = AssetId<Image>> + '_ {
+        self.regions.iter().filter_map(|entry| entry.mask)
+    }
+
+    /// Points each masked blur region at the layer of the mask texture holding its mask.
+    pub(crate) fn set_mask_layers(&mut self, layer: impl Fn(AssetId<Image>) -> Option<usize>) {
+        for entry in &mut self.regions {
+            entry.region.mask_layer = entry.mask.and_then(&layer).map_or(-1, |layer| layer as i32);
+        }
+    }
+
+    fn push(&mut self, region: ComputedBlurRegion, mask: Option<&BlurRegionMask>, settings: &BlurRegionSettings) {
+        self.regions.push(BlurRegionEntry {
+            region,
+            priority: settings.priority,
+            mask: mask.map(|mask| mask.image.id()),
+        });
+    }
+
     fn clear(&mut self) {
-        self.current_regions_count = 0;
+        self.regions.clear();
+        #[cfg(feature = "bevy_ui")]
+        {
+            self.ui_layer = None;
+        }
+    }
+
+    /// Applies the overflow policy so that at most `limit` blur regions remain, returning the
+    /// number of blur regions over the limit.
+    fn overflow(&mut self, limit: usize) -> usize {
+        if self.regions.len() <= limit {
+            return 0;
+        }
+
+        let excess = self.regions.len() - limit;
+        match self.overflow_policy {
+            BlurRegionOverflowPolicy::DropNewest => {
+                self.regions.truncate(limit);
+                excess
+            }
+            BlurRegionOverflowPolicy::DropSmallest => {
+                self.drop_lowest(excess, |entry| FloatOrd(entry.region.area()));
+                excess
+            }
+            BlurRegionOverflowPolicy::Priority => {
+                self.drop_lowest(excess, |entry| entry.priority);
+                excess
+            }
+            BlurRegionOverflowPolicy::MergeIntoBoundingBox => {
+                if limit == 0 {
+                    self.regions.clear();
+                    return excess;
+                }
+
+                // The bounding box of a cut-out isn't meaningful, so inverted blur regions are dropped
+                // rather than merged.
+                let merged = self.regions.split_off(limit - 1);
+                let mut merged = merged.into_iter().filter(|entry| entry.region.inverted == 0);
+                let Some(mut bounds) = merged.next() else {
+                    return excess;
+                };
+                bounds.region.border_radii = Vec4::ZERO;
+                bounds.region.rotation = Vec2::X;
+                bounds.region.shape = SHAPE_ROUNDED_BOX;
+                bounds.region.gradient_kind = GRADIENT_NONE;
+                bounds.mask = None;
+                for entry in merged {
+                    bounds.region.min_x = bounds.region.min_x.min(entry.region.min_x);
+                    bounds.region.max_x = bounds.region.max_x.max(entry.region.max_x);
+                    bounds.region.min_y = bounds.region.min_y.min(entry.region.min_y);
+                    bounds.region.max_y = bounds.region.max_y.max(entry.region.max_y);
+                    bounds.region.circle_of_confusion =
+                        bounds.region.circle_of_confusion.max(entry.region.circle_of_confusion);
+                    bounds.region.feather = bounds.region.feather.max(entry.region.feather);
+                    bounds.priority = bounds.priority.max(entry.priority);
+                }
+                let half_size = Vec2::new(
+                    bounds.region.max_x - bounds.region.min_x,
+                    bounds.region.max_y - bounds.region.min_y,
+                ) * 0.5;
+                bounds.region.shape_params = [half_size.extend(0.0).extend(0.0), Vec4::ZERO];
+                self.regions.push(bounds);
+                excess
+            }
+        }
+    }
+
+    /// Drops the `count` blur regions with the lowest key, dropping newer blur regions first when
+    /// keys are equal. The order of the remaining blur regions is preserved.
+    fn drop_lowest<K: Ord>(&mut self, count: usize, key: impl Fn(&BlurRegionEntry) -> K) {
+        let mut order: Vec<usize> = (0..self.regions.len()).collect();
+        order.sort_by(|&a, &b| key(&self.regions[a]).cmp(&key(&self.regions[b])).then(b.cmp(&a)));
+
+        let mut dropped = vec![false; self.regions.len()];
+        for &index in &order[..count] {
+            dropped[index] = true;
+        }
+
+        let mut index = 0;
+        self.regions.retain(|_| {
+            index += 1;
+            !dropped[index - 1]
+        });
     }
 }
 
-fn clear_blur_regions<const N: usize>(mut blur_region_cameras: Query<&mut BlurRegionsCamera<N>>) {
+fn clear_blur_regions(mut blur_region_cameras: Query<&mut BlurRegionsCamera>) {
     for mut blur_region in &mut blur_region_cameras {
         blur_region.clear();
     }
 }
 
-pub struct BlurRegionsPlugin<const N: usize>;
+fn apply_blur_regions_overflow_policy(
+    render_device: Option<Res<RenderDevice>>,
+    mut blur_region_cameras: Query<(Entity, &mut BlurRegionsCamera)>,
+    mut overflow_events: EventWriter<BlurRegionsOverflow>,
+) {
+    let platform_limit = render_device
+        .filter(|render_device| render_device.limits().max_storage_buffers_per_shader_stage == 0)
+        .map(|_| UNIFORM_MAX_BLUR_REGIONS_COUNT);
 
-impl Default for BlurRegionsPlugin<DEFAULT_MAX_BLUR_REGIONS_COUNT> {
-    fn default() -> Self {
-        BlurRegionsPlugin::<DEFAULT_MAX_BLUR_REGIONS_COUNT>
+    for (camera, mut blur_regions) in &mut blur_region_cameras {
+        let Some(limit) = [blur_regions.max_regions, platform_limit].into_iter().flatten().min() else {
+            continue;
+        };
+
+        let count = blur_regions.overflow(limit);
+        if count > 0 {
+            overflow_events.send(BlurRegionsOverflow { camera, count });
+        }
     }
 }
 
-impl<const N: usize> Plugin for BlurRegionsPlugin<N> {
+#[derive(Default)]
+pub struct BlurRegionsPlugin {
+    /// Where the blur regions are rendered in the render graph of each camera.
+    pub graph_placement: BlurRegionsGraphPlacement,
+}
+
+impl BlurRegionsPlugin {
+    pub fn with_graph_placement(mut self, graph_placement: BlurRegionsGraphPlacement) -> Self {
+        self.graph_placement = graph_placement;
+        self
+    }
+}
+
+impl Plugin for BlurRegionsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreUpdate, clear_blur_regions::<N>).add_plugins(crate::shader::BlurRegionsShaderPlugin::<N>);
+        app.add_event::<BlurRegionsOverflow>()
+            .configure_sets(
+                Last,
+                (BlurRegionsSystems::Compute, BlurRegionsSystems::Overflow).chain(),
+            )
+            .add_systems(PreUpdate, clear_blur_regions)
+            .add_systems(
+                Last,
+                apply_blur_regions_overflow_policy.in_set(BlurRegionsSystems::Overflow),
+            )
+            .add_plugins((
+                crate::mask::BlurRegionsMaskPlugin,
+                crate::pyramid::BlurPyramidPlugin,
+                crate::screen::BlurRegionsScreenPlugin,
+                crate::shader::BlurRegionsShaderPlugin {
+                    graph_placement: self.graph_placement.clone(),
+                },
+                crate::blur_mask::BlurMaskPlugin {
+                    graph_placement: self.graph_placement.clone(),
+                },
+                crate::compute::BlurComputePlugin,
+                crate::transition::BlurTransitionPlugin,
+                crate::world::BlurRegionsWorldPlugin,
+            ));
 
         #[cfg(feature = "bevy_ui")]
-        app.add_plugins(crate::bevy_ui::BlurRegionsBevyUiPlugin::<N>);
+        app.add_plugins(crate::bevy_ui::BlurRegionsBevyUiPlugin);
+
+        #[cfg(feature = "bevy_ui")]
+        if self.graph_placement == BlurRegionsGraphPlacement::UiBackdrop {
+            app.add_plugins(crate::ui_backdrop::BlurRegionsUiBackdropPlugin);
+        }
 
         #[cfg(feature = "egui")]
-        app.add_plugins(crate::egui::BlurRegionsEguiPlugin::<N>);
+        app.add_plugins(crate::egui::BlurRegionsEguiPlugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera_with_regions(policy: BlurRegionOverflowPolicy, regions: &[(Rect, i32)]) -> BlurRegionsCamera {
+        let mut camera = BlurRegionsCamera {
+            overflow_policy: policy,
+            ..default()
+        };
+        for &(rect, priority) in regions {
+            let settings = BlurRegionSettings {
+                priority,
+                ..camera.settings()
+            };
+            camera.blur_with(rect, settings);
+        }
+        camera
+    }
+
+    fn bounds(camera: &BlurRegionsCamera) -> Vec<Rect> {
+        camera.regions().map(|region| Rect::new(region.min_x, region.min_y, region.max_x, region.max_y)).collect()
+    }
+
+    const SMALL: Rect = Rect {
+        min: Vec2::new(0.0, 0.0),
+        max: Vec2::new(10.0, 10.0),
+    };
+    const MEDIUM: Rect = Rect {
+        min: Vec2::new(100.0, 100.0),
+        max: Vec2::new(150.0, 150.0),
+    };
+    const LARGE: Rect = Rect {
+        min: Vec2::new(200.0, 0.0),
+        max: Vec2::new(300.0, 100.0),
+    };
+
+    fn region_bounds(region: &ComputedBlurRegion) -> Rect {
+        Rect::new(region.min_x, region.min_y, region.max_x, region.max_y)
+    }
+
+    fn assert_rect_eq(a: Rect, b: Rect) {
+        assert!(
+            a.min.abs_diff_eq(b.min, 1e-3) && a.max.abs_diff_eq(b.max, 1e-3),
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn rotated_regions_grow_their_bounds_to_fit() {
+        let rect = Rect::from_center_size(Vec2::new(300.0, 200.0), Vec2::new(200.0, 100.0));
+        let region = ComputedBlurRegion::rounded_box(rect, Vec4::ZERO, &default());
+
+        let quarter = region.clone().rotated(rect.center(), Rot2::degrees(90.0));
+        assert_rect_eq(
+            region_bounds(&quarter),
+            Rect::from_center_size(rect.center(), Vec2::new(100.0, 200.0)),
+        );
+        // The shape is evaluated in its local frame, so its params are unchanged.
+        assert_eq!(quarter.shape_params, region.shape_params);
+        assert!(quarter.rotation.abs_diff_eq(Vec2::new(0.0, -1.0), 1e-6));
+
+        let eighth = region.rotated(rect.center(), Rot2::degrees(45.0));
+        let size = Vec2::splat(300.0 * std::f32::consts::FRAC_1_SQRT_2);
+        assert_rect_eq(region_bounds(&eighth), Rect::from_center_size(rect.center(), size));
+    }
+
+    #[test]
+    fn rotated_regions_orbit_their_origin() {
+        let rect = Rect::from_center_size(Vec2::new(10.0, 0.0), Vec2::splat(2.0));
+        let region = ComputedBlurRegion::rounded_box(rect, Vec4::ZERO, &default());
+
+        // Counterclockwise on the screen, which is y down, moves a point right of the origin up.
+        let rotated = region.rotated(Vec2::ZERO, Rot2::degrees(90.0));
+        assert_rect_eq(
+            region_bounds(&rotated),
+            Rect::from_center_size(Vec2::new(0.0, -10.0), Vec2::splat(2.0)),
+        );
+    }
+
+    #[test]
+    fn triangle_regions_are_bounded_by_their_vertices() {
+        let triangle = Triangle2d::new(Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::new(0.0, 50.0));
+        let region = ComputedBlurRegion::shape(Vec2::new(200.0, 200.0), triangle.into(), &default());
+
+        assert_eq!(region.shape, SHAPE_TRIANGLE);
+        // The triangle is flipped to y down, so it extends above its origin on the screen.
+        assert_rect_eq(region_bounds(&region), Rect::new(200.0, 150.0, 300.0, 200.0));
+        // The vertices are relative to the center of the bounds.
+        assert_eq!(
+            region.shape_params,
+            [Vec4::new(-50.0, 25.0, 50.0, 25.0), Vec4::new(-50.0, -25.0, 0.0, 0.0)]
+        );
+    }
+
+    /// Evaluates a gradient at a point relative to the center of its blur region, as the shader does.
+    fn gradient_progress(gradient: BlurRegionGradient, half_size: Vec2, point: Vec2) -> f32 {
+        let (kind, params, _) = gradient.to_shader_values(half_size);
+        match kind {
+            GRADIENT_LINEAR => point.dot(params.xy()) + params.z,
+            GRADIENT_RADIAL => (point - params.xy()).length() * params.z,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn linear_gradients_span_the_blur_region() {
+        let half_size = Vec2::new(100.0, 50.0);
+
+        let vertical = BlurRegionGradient::vertical(0.0, 1.0);
+        assert_eq!(vertical.to_shader_values(half_size).2, Vec2::new(0.0, 1.0));
+        // Blur regions are y down, so the top is at negative y.
+        assert_eq!(gradient_progress(vertical, half_size, Vec2::new(0.0, -50.0)), 0.0);
+        assert_eq!(gradient_progress(vertical, half_size, Vec2::new(30.0, 0.0)), 0.5);
+        assert_eq!(gradient_progress(vertical, half_size, Vec2::new(-30.0, 50.0)), 1.0);
+
+        let horizontal = BlurRegionGradient::horizontal(0.0, 1.0);
+        assert_eq!(gradient_progress(horizontal, half_size, Vec2::new(-100.0, 20.0)), 0.0);
+        assert_eq!(gradient_progress(horizontal, half_size, Vec2::new(100.0, -20.0)), 1.0);
+
+        // A diagonal gradient reaches its end at the far corner.
+        let diagonal = BlurRegionGradient::Linear {
+            direction: Dir2::from_xy(1.0, -1.0).unwrap(),
+            start: 0.0,
+            end: 1.0,
+        };
+        assert!(gradient_progress(diagonal, half_size, Vec2::new(-100.0, -50.0)).abs() < 1e-6);
+        assert!((gradient_progress(diagonal, half_size, Vec2::new(100.0, 50.0)) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn radial_gradients_end_at_the_farthest_corner() {
+        let half_size = Vec2::new(100.0, 50.0);
+
+        let centered = BlurRegionGradient::Radial {
+            center: Vec2::splat(0.5),
+            start: 1.0,
+            end: 0.0,
+        };
+        assert_eq!(centered.to_shader_values(half_size).2, Vec2::new(1.0, 0.0));
+        assert_eq!(gradient_progress(centered, half_size, Vec2::ZERO), 0.0);
+        assert!((gradient_progress(centered, half_size, Vec2::new(-100.0, 50.0)) - 1.0).abs() < 1e-6);
+
+        // From the top left corner, the farthest corner is the bottom right one.
+        let corner = BlurRegionGradient::Radial {
+            center: Vec2::ZERO,
+            start: 0.0,
+            end: 1.0,
+        };
+        assert_eq!(gradient_progress(corner, half_size, Vec2::new(-100.0, -50.0)), 0.0);
+        assert!((gradient_progress(corner, half_size, Vec2::new(100.0, 50.0)) - 1.0).abs() < 1e-6);
+        assert!(gradient_progress(corner, half_size, Vec2::new(100.0, -50.0)) < 1.0);
+    }
+
+    #[test]
+    fn scissor_rects_cover_the_visible_bounds_of_regions() {
+        let mut camera = BlurRegionsCamera::default();
+        camera.blur(Rect::new(10.2, 20.7, 30.5, 40.1));
+        // Partially offscreen, clamped to the render target.
+        camera.blur(Rect::new(-50.0, 90.0, 50.0, 150.0));
+        // Fully offscreen, skipped.
+        camera.blur(Rect::new(300.0, 0.0, 400.0, 50.0));
+
+        let rects = camera.scissor_rects(UVec2::new(200, 100)).unwrap();
+        assert_eq!(rects, [URect::new(10, 20, 31, 41), URect::new(0, 90, 50, 100),]);
+    }
+
+    #[test]
+    fn scissor_rects_are_skipped_for_unbounded_blurs() {
+        let target_size = UVec2::new(200, 100);
+
+        let mut camera = BlurRegionsCamera::default();
+        camera.blur(SMALL);
+        camera.blur_except(MEDIUM);
+        assert_eq!(camera.scissor_rects(target_size), None);
+
+        let mut camera = BlurRegionsCamera::default().with_tilt_shift(TiltShift::default());
+        camera.blur(SMALL);
+        assert_eq!(camera.scissor_rects(target_size), None);
+
+        // A tilt-shift without any blur doesn't cover the screen.
+        camera.tilt_shift = Some(TiltShift {
+            circle_of_confusion: 0.0,
+            ..default()
+        });
+        assert_eq!(camera.scissor_rects(target_size), Some(vec![URect::new(0, 0, 10, 10)]));
+    }
+
+    #[test]
+    fn overflow_within_limit_keeps_every_region() {
+        let mut camera = camera_with_regions(BlurRegionOverflowPolicy::DropNewest, &[(SMALL, 0), (MEDIUM, 0)]);
+        assert_eq!(camera.overflow(2), 0);
+        assert_eq!(bounds(&camera), [SMALL, MEDIUM]);
+    }
+
+    #[test]
+    fn overflow_drop_newest() {
+        let mut camera = camera_with_regions(
+            BlurRegionOverflowPolicy::DropNewest,
+            &[(LARGE, 0), (SMALL, 0), (MEDIUM, 0)],
+        );
+        assert_eq!(camera.overflow(1), 2);
+        assert_eq!(bounds(&camera), [LARGE]);
+    }
+
+    #[test]
+    fn overflow_drop_smallest() {
+        let mut camera = camera_with_regions(
+            BlurRegionOverflowPolicy::DropSmallest,
+            &[(MEDIUM, 0), (SMALL, 0), (LARGE, 0)],
+        );
+        assert_eq!(camera.overflow(2), 1);
+        assert_eq!(bounds(&camera), [MEDIUM, LARGE]);
+    }
+
+    #[test]
+    fn overflow_priority_drops_newest_of_equal_priority() {
+        let mut camera = camera_with_regions(
+            BlurRegionOverflowPolicy::Priority,
+            &[(SMALL, 1), (MEDIUM, 0), (LARGE, 0)],
+        );
+        assert_eq!(camera.overflow(2), 1);
+        assert_eq!(bounds(&camera), [SMALL, MEDIUM]);
+
+        assert_eq!(camera.overflow(1), 1);
+        assert_eq!(bounds(&camera), [SMALL]);
+    }
+
+    #[test]
+    fn overflow_merge_into_bounding_box() {
+        let mut camera = camera_with_regions(
+            BlurRegionOverflowPolicy::MergeIntoBoundingBox,
+            &[(SMALL, 0), (MEDIUM, 0), (LARGE, 0)],
+        );
+        // Two blur regions are merged into one, so only one is lost.
+        assert_eq!(camera.overflow(2), 1);
+        assert_eq!(bounds(&camera), [SMALL, Rect::new(100.0, 0.0, 300.0, 150.0)]);
+    }
+
+    #[test]
+    fn overflow_merge_drops_inverted_regions() {
+        let mut camera = camera_with_regions(BlurRegionOverflowPolicy::MergeIntoBoundingBox, &[(SMALL, 0)]);
+        camera.blur_except(MEDIUM);
+        camera.blur(LARGE);
+        assert_eq!(camera.overflow(2), 1);
+        assert_eq!(bounds(&camera), [SMALL, LARGE]);
+    }
+
+    #[test]
+    fn bin_regions_puts_inverted_and_screen_spanning_regions_in_every_tile() {
+        let target_size = UVec2::new(200, 150);
+        let mut camera = BlurRegionsCamera::default();
+        for _ in 0..MAX_UNTILED_BLUR_REGIONS_COUNT {
+            camera.blur(SMALL);
+        }
+        let inverted = camera.regions().len() as u32;
+        camera.blur_except(SMALL);
+        let spanning = inverted + 1;
+        camera.blur(Rect::new(-50.0, -50.0, 500.0, 500.0));
+
+        let mut data = Vec::new();
+        let grid = camera.bin_regions(target_size, &mut data);
+        assert_eq!(grid, UVec2::new(4, 3));
+
+        for tile in 0..(grid.x * grid.y) as usize {
+            let offset = data[tile * 2] as usize;
+            let count = data[tile * 2 + 1] as usize;
+            let regions = &data[offset..offset + count];
+            assert!(
+                regions.contains(&inverted),
+                "tile {tile} is missing the inverted region"
+            );
+            assert!(
+                regions.contains(&spanning),
+                "tile {tile} is missing the screen spanning region"
+            );
+            // The small regions only cover the first tile.
+            assert_eq!(regions.contains(&0), tile == 0);
+        }
+    }
+
+    #[test]
+    fn bin_regions_covers_partial_tiles() {
+        // Neither side is a multiple of the tile size, so the last column and row are partial.
+        let target_size = UVec2::new(130, 70);
+        let mut camera = BlurRegionsCamera::default();
+        for _ in 0..MAX_UNTILED_BLUR_REGIONS_COUNT {
+            camera.blur(SMALL);
+        }
+        let corner = camera.regions().len() as u32;
+        camera.blur(Rect::new(129.0, 69.0, 200.0, 200.0));
+
+        let mut data = Vec::new();
+        let grid = camera.bin_regions(target_size, &mut data);
+        assert_eq!(grid, UVec2::new(3, 2));
+        assert_eq!(
+            data.len(),
+            (grid.x * grid.y) as usize * 2 + MAX_UNTILED_BLUR_REGIONS_COUNT + 1
+        );
+
+        let last_tile = (grid.x * grid.y - 1) as usize;
+        let offset = data[last_tile * 2] as usize;
+        let count = data[last_tile * 2 + 1] as usize;
+        assert_eq!(&data[offset..offset + count], [corner]);
+    }
+
+    #[test]
+    fn bin_regions_skips_binning_few_regions() {
+        let mut camera = BlurRegionsCamera::default();
+        camera.blur(SMALL);
+        let mut data = Vec::new();
+        assert_eq!(camera.bin_regions(UVec2::new(200, 150), &mut data), UVec2::ZERO);
+        assert!(data.is_empty());
     }
 }
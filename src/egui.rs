@@ -4,14 +4,14 @@ use bevy_egui::egui;
 use bevy_egui::egui::Rounding;
 use bevy_egui::EguiContext;
 
-use crate::core::DEFAULT_MAX_BLUR_REGIONS_COUNT;
 use crate::BlurRegionsCamera;
+use crate::BlurRegionsSystems;
 
-pub struct BlurRegionsEguiPlugin<const N: usize>;
+pub struct BlurRegionsEguiPlugin;
 
-impl<const N: usize> Plugin for BlurRegionsEguiPlugin<N> {
+impl Plugin for BlurRegionsEguiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Last, extract_egui_blurs::<N>);
+        app.add_systems(Last, extract_egui_blurs.in_set(BlurRegionsSystems::Compute));
     }
 }
 
@@ -22,35 +22,27 @@ enum EguiBlurTarget {
 }
 
 #[derive(Clone)]
-struct EguiBlurRegions<const N: usize> {
+struct EguiBlurRegions {
     target: EguiBlurTarget,
-    current_regions_count: u32,
-    regions: [(Rect, Vec4); N],
+    regions: Vec<(Rect, Vec4)>,
 }
 
-impl<const N: usize> Default for EguiBlurRegions<N> {
+impl Default for EguiBlurRegions {
     fn default() -> Self {
         EguiBlurRegions {
             target: EguiBlurTarget::DefaultCamera,
-            current_regions_count: 0,
-            regions: std::array::from_fn(|_| (Rect::new(-1.0, -1.0, -1.0, -1.0), Vec4::ZERO)),
+            regions: Vec::new(),
         }
     }
 }
 
-impl<const N: usize> EguiBlurRegions<N> {
+impl EguiBlurRegions {
     pub fn blur(&mut self, rect: Rect, rounding: Vec4) {
-        if self.current_regions_count == N as u32 {
-            warn!("Blur region ignored as the max blur region count has already been reached");
-            return;
-        }
-
-        self.regions[self.current_regions_count as usize] = (rect, rounding);
-        self.current_regions_count += 1;
+        self.regions.push((rect, rounding));
     }
 
     fn clear(&mut self) {
-        self.current_regions_count = 0;
+        self.regions.clear();
     }
 }
 
@@ -61,25 +53,12 @@ pub trait EguiWindowBlurExt {
         add_contents: impl FnOnce(&mut egui::Ui) -> R,
     ) -> Option<egui::InnerResponse<Option<R>>>;
 
-    fn show_with_blur_n<R, const N: usize>(
-        self,
-        ctx: &egui::Context,
-        add_contents: impl FnOnce(&mut egui::Ui) -> R,
-    ) -> Option<egui::InnerResponse<Option<R>>>;
-
     fn show_with_blur_on_camera<R>(
         self,
         camera_entity: Entity,
         ctx: &egui::Context,
         add_contents: impl FnOnce(&mut egui::Ui) -> R,
     ) -> Option<egui::InnerResponse<Option<R>>>;
-
-    fn show_with_blur_on_camera_n<R, const N: usize>(
-        self,
-        camera_entity: Entity,
-        ctx: &egui::Context,
-        add_contents: impl FnOnce(&mut egui::Ui) -> R,
-    ) -> Option<egui::InnerResponse<Option<R>>>;
 }
 
 fn get_egui_blur_rect<R>(
@@ -123,19 +102,11 @@ impl<'open> EguiWindowBlurExt for egui::Window<'open> {
         self,
         ctx: &egui::Context,
         add_contents: impl FnOnce(&mut egui::Ui) -> R,
-    ) -> Option<egui::InnerResponse<Option<R>>> {
-        self.show_with_blur_n::<R, DEFAULT_MAX_BLUR_REGIONS_COUNT>(ctx, add_contents)
-    }
-
-    fn show_with_blur_n<R, const N: usize>(
-        self,
-        ctx: &egui::Context,
-        add_contents: impl FnOnce(&mut egui::Ui) -> R,
     ) -> Option<egui::InnerResponse<Option<R>>> {
         let (response, blur_rect, rounding) = get_egui_blur_rect(self, ctx, add_contents)?;
 
         ctx.memory_mut(|mem| {
-            let egui_blur_regions: &mut EguiBlurRegions<N> = mem.data.get_temp_mut_or_default(egui::Id::NULL);
+            let egui_blur_regions: &mut EguiBlurRegions = mem.data.get_temp_mut_or_default(egui::Id::NULL);
             egui_blur_regions.target = EguiBlurTarget::DefaultCamera;
             egui_blur_regions.blur(blur_rect, Vec4::new(rounding.nw, rounding.ne, rounding.se, rounding.sw));
         });
@@ -148,20 +119,11 @@ impl<'open> EguiWindowBlurExt for egui::Window<'open> {
         camera_entity: Entity,
         ctx: &egui::Context,
         add_contents: impl FnOnce(&mut egui::Ui) -> R,
-    ) -> Option<egui::InnerResponse<Option<R>>> {
-        self.show_with_blur_on_camera_n::<R, DEFAULT_MAX_BLUR_REGIONS_COUNT>(camera_entity, ctx, add_contents)
-    }
-
-    fn show_with_blur_on_camera_n<R, const N: usize>(
-        self,
-        camera_entity: Entity,
-        ctx: &egui::Context,
-        add_contents: impl FnOnce(&mut egui::Ui) -> R,
     ) -> Option<egui::InnerResponse<Option<R>>> {
         let (response, blur_rect, rounding) = get_egui_blur_rect(self, ctx, add_contents)?;
 
         ctx.memory_mut(|mem| {
-            let egui_blur_regions: &mut EguiBlurRegions<N> = mem.data.get_temp_mut_or_default(egui::Id::NULL);
+            let egui_blur_regions: &mut EguiBlurRegions = mem.data.get_temp_mut_or_default(egui::Id::NULL);
             egui_blur_regions.target = EguiBlurTarget::Entity(camera_entity);
             egui_blur_regions.blur(blur_rect, Vec4::new(rounding.nw, rounding.ne, rounding.se, rounding.sw));
         });
@@ -170,31 +132,27 @@ impl<'open> EguiWindowBlurExt for egui::Window<'open> {
     }
 }
 
-pub fn extract_egui_blurs<const N: usize>(
+pub fn extract_egui_blurs(
     mut contexts: Query<&'static mut EguiContext>,
-    mut blur_region_cameras: Query<&mut BlurRegionsCamera<N>>,
+    mut blur_region_cameras: Query<&mut BlurRegionsCamera>,
 ) {
     for mut context in &mut contexts {
         let ctx = context.get_mut();
 
         ctx.memory_mut(|mem| {
-            let egui_blur_regions: &mut EguiBlurRegions<N> = mem.data.get_temp_mut_or_default(egui::Id::NULL);
+            let egui_blur_regions: &mut EguiBlurRegions = mem.data.get_temp_mut_or_default(egui::Id::NULL);
 
             match egui_blur_regions.target {
                 EguiBlurTarget::DefaultCamera => {
                     if let Ok(mut blur_regions) = blur_region_cameras.get_single_mut() {
-                        blur_regions.rounded_blur_all(
-                            &egui_blur_regions.regions[0..(egui_blur_regions.current_regions_count as usize)],
-                        );
+                        blur_regions.rounded_blur_all(&egui_blur_regions.regions);
                     } else {
                         debug!("No default BlurRegionsCamera exists, skipping blurring.");
                     }
                 }
                 EguiBlurTarget::Entity(entity) => {
                     if let Ok(mut blur_regions) = blur_region_cameras.get_mut(entity) {
-                        blur_regions.rounded_blur_all(
-                            &egui_blur_regions.regions[0..(egui_blur_regions.current_regions_count as usize)],
-                        );
+                        blur_regions.rounded_blur_all(&egui_blur_regions.regions);
                     } else {
                         debug!("No BlurRegionsCamera exists for entity {entity:?}, skipping blurring.");
                     }
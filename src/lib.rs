@@ -1,25 +1,85 @@
 #[cfg(feature = "bevy_ui")]
 mod bevy_ui;
+mod blur_mask;
+mod compute;
 mod core;
 #[cfg(feature = "egui")]
 mod egui;
+mod mask;
+mod pyramid;
+mod screen;
 mod shader;
+mod tilt_shift;
+mod transition;
+#[cfg(feature = "bevy_ui")]
+mod ui_backdrop;
+mod world;
 
 pub mod prelude {
+    pub use super::BlurAlgorithm;
+    pub use super::BlurMask;
     pub use super::BlurRegion;
+    pub use super::BlurRegionBlendMode;
+    pub use super::BlurRegionGradient;
+    pub use super::BlurRegionMask;
+    pub use super::BlurRegionOverflowPolicy;
+    pub use super::BlurRegionSettings;
+    pub use super::BlurRegionShape;
     pub use super::BlurRegionsCamera;
+    pub use super::BlurRegionsGraphPlacement;
     pub use super::BlurRegionsPlugin;
+    pub use super::BlurRenderMode;
+    pub use super::BlurTransition;
+    pub use super::BlurTransitionClock;
+    pub use super::BlurTransitionEasing;
+    pub use super::BlurTransitionState;
+    pub use super::BoundsBlurRegion;
+    #[allow(deprecated)]
     pub use super::DefaultBlurRegionsCamera;
+    pub use super::ScreenBlurRegion;
+    pub use super::TiltShift;
+    pub use super::WorldBlurRegion;
+
+    #[cfg(feature = "bevy_ui")]
+    pub use super::UiImageBlurMask;
 
     #[cfg(feature = "egui")]
     pub use super::EguiWindowBlurExt;
 }
 
+pub use blur_mask::BlurMask;
+pub use blur_mask::BlurMaskLabel;
+pub use core::BlurAlgorithm;
 pub use core::BlurRegion;
+pub use core::BlurRegionBlendMode;
+pub use core::BlurRegionGradient;
+pub use core::BlurRegionOverflowPolicy;
+pub use core::BlurRegionSettings;
+pub use core::BlurRegionShape;
 pub use core::BlurRegionsCamera;
+pub use core::BlurRegionsOverflow;
 pub use core::BlurRegionsPlugin;
+pub use core::BlurRegionsSystems;
+pub use core::BlurRenderMode;
+#[allow(deprecated)]
 pub use core::DefaultBlurRegionsCamera;
+pub use mask::BlurRegionMask;
+pub use screen::ScreenBlurRegion;
+pub use shader::BlurRegionsGraphPlacement;
 pub use shader::BlurRegionsLabel;
+pub use tilt_shift::TiltShift;
+pub use transition::BlurTransition;
+pub use transition::BlurTransitionClock;
+pub use transition::BlurTransitionEasing;
+pub use transition::BlurTransitionFinished;
+pub use transition::BlurTransitionState;
+pub use world::BoundsBlurRegion;
+pub use world::WorldBlurRegion;
+
+#[cfg(feature = "bevy_ui")]
+pub use crate::bevy_ui::UiImageBlurMask;
+#[cfg(feature = "bevy_ui")]
+pub use crate::ui_backdrop::BlurredUiLabel;
 
 #[cfg(feature = "egui")]
 pub use crate::egui::EguiWindowBlurExt;
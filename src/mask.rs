@@ -0,0 +1,287 @@
+use bevy::prelude::*;
+use bevy::render::extract_resource::ExtractResource;
+use bevy::render::extract_resource::ExtractResourcePlugin;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::Extent3d;
+use bevy::render::render_resource::TextureDimension;
+use bevy::render::render_resource::TextureFormat;
+use bevy::render::render_resource::TextureViewDescriptor;
+use bevy::render::render_resource::TextureViewDimension;
+
+use crate::BlurRegionsCamera;
+use crate::BlurRegionsSystems;
+
+/// The width and height of each layer of the mask texture. Masks are resampled to this size, which
+/// is plenty for the soft edges of a blur.
+const MASK_RESOLUTION: u32 = 256;
+
+pub struct BlurRegionsMaskPlugin;
+
+impl Plugin for BlurRegionsMaskPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BlurRegionMasks>()
+            .add_plugins(ExtractResourcePlugin::<BlurRegionMasks>::default())
+            .add_systems(Last, prepare_blur_region_masks.after(BlurRegionsSystems::Overflow));
+    }
+}
+
+/// An image whose alpha channel is used as the shape of a blur region. Transparent areas of the
+/// image are left unblurred.
+///
+/// Images with 8 bit RGBA, BGRA or single channel formats are supported.
+#[derive(Debug, Clone)]
+pub struct BlurRegionMask {
+    pub image: Handle<Image>,
+    /// The area of the image that is mapped onto the blur region, from `(0, 0)` at the top left of
+    /// the image to `(1, 1)` at the bottom right.
+    pub uv_rect: Rect,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+impl BlurRegionMask {
+    pub fn new(image: Handle<Image>) -> Self {
+        BlurRegionMask {
+            image,
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    /// The corners of the mask in uv coordinates, in the order they are mapped onto the top left
+    /// and bottom right of the blur region.
+    pub(crate) fn uv(&self) -> Vec4 {
+        let (left, right) = if self.flip_x {
+            (self.uv_rect.max.x, self.uv_rect.min.x)
+        } else {
+            (self.uv_rect.min.x, self.uv_rect.max.x)
+        };
+        let (top, bottom) = if self.flip_y {
+            (self.uv_rect.max.y, self.uv_rect.min.y)
+        } else {
+            (self.uv_rect.min.y, self.uv_rect.max.y)
+        };
+        Vec4::new(left, top, right, bottom)
+    }
+}
+
+impl From<Handle<Image>> for BlurRegionMask {
+    fn from(image: Handle<Image>) -> Self {
+        BlurRegionMask::new(image)
+    }
+}
+
+/// The masks used by blur regions, packed into the layers of a single texture array that is shared
+/// by every camera.
+#[derive(Resource, Clone, ExtractResource)]
+pub(crate) struct BlurRegionMasks {
+    pub(crate) image: Handle<Image>,
+    layers: Vec<AssetId<Image>>,
+}
+
+impl FromWorld for BlurRegionMasks {
+    fn from_world(world: &mut World) -> Self {
+        let image = world.resource_mut::<Assets<Image>>().add(build_mask_texture(&[], None));
+        BlurRegionMasks {
+            image,
+            layers: Vec::new(),
+        }
+    }
+}
+
+fn prepare_blur_region_masks(
+    mut masks: ResMut<BlurRegionMasks>,
+    mut images: ResMut<Assets<Image>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    mut blur_region_cameras: Query<&mut BlurRegionsCamera>,
+) {
+    let mut layers: Vec<AssetId<Image>> = blur_region_cameras.iter().flat_map(|camera| camera.masks()).collect();
+    layers.sort();
+    layers.dedup();
+
+    // Masks that haven't loaded yet are left empty, and filled in once they load.
+    let masks_changed = image_events.read().any(|event| match event {
+        AssetEvent::Added { id } | AssetEvent::Modified { id } | AssetEvent::LoadedWithDependencies { id } => {
+            layers.contains(id)
+        }
+        _ => false,
+    });
+    if masks_changed || layers != masks.layers {
+        let texture = build_mask_texture(&layers, Some(&images));
+        images.insert(&masks.image, texture);
+        masks.layers = layers;
+    }
+
+    for mut blur_regions in &mut blur_region_cameras {
+        blur_regions.set_mask_layers(|mask| masks.layers.binary_search(&mask).ok());
+    }
+}
+
+/// Builds a texture array with the alpha channel of each mask resampled into its own layer.
+fn build_mask_texture(layers: &[AssetId<Image>], images: Option<&Assets<Image>>) -> Image {
+    let layer_size = (MASK_RESOLUTION * MASK_RESOLUTION) as usize;
+    // The texture always has at least one layer so that it can be bound.
+    let mut data = vec![0; layer_size * layers.len().max(1)];
+
+    for (layer, id) in layers.iter().enumerate() {
+        let Some(image) = images.and_then(|images| images.get(*id)) else {
+            continue;
+        };
+
+        let Some((pixel_size, alpha_offset)) = alpha_channel(image.texture_descriptor.format) else {
+            warn_once!(
+                "Blur region mask has unsupported format {:?}, masks must be 8 bit RGBA, BGRA or single channel images",
+                image.texture_descriptor.format
+            );
+            continue;
+        };
+
+        let size = image.size();
+        if size.x == 0 || size.y == 0 {
+            continue;
+        }
+
+        // Images without their data on the CPU, such as render targets, can't be read.
+        let expected_len = (size.x * size.y) as usize * pixel_size;
+        if image.data.len() < expected_len {
+            warn_once!(
+                "Blur region mask has {} bytes of data, but {expected_len} are needed for a {}x{} image, masks must be images with their data on the CPU",
+                image.data.len(),
+                size.x,
+                size.y
+            );
+            continue;
+        }
+
+        let alpha = |x: u32, y: u32| image.data[(y * size.x + x) as usize * pixel_size + alpha_offset] as u32;
+        let layer_data = &mut data[layer * layer_size..][..layer_size];
+        for y in 0..MASK_RESOLUTION {
+            for x in 0..MASK_RESOLUTION {
+                // Average the pixels of the image covered by this texel, or take the nearest pixel
+                // when the image is smaller than the texture.
+                let (x0, x1) = source_range(x, size.x);
+                let (y0, y1) = source_range(y, size.y);
+                let mut sum = 0;
+                for source_y in y0..y1 {
+                    for source_x in x0..x1 {
+                        sum += alpha(source_x, source_y);
+                    }
+                }
+                layer_data[(y * MASK_RESOLUTION + x) as usize] = (sum / ((x1 - x0) * (y1 - y0))) as u8;
+            }
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: MASK_RESOLUTION,
+            height: MASK_RESOLUTION,
+            depth_or_array_layers: layers.len().max(1) as u32,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    // A texture with a single layer would otherwise be viewed as a 2d texture rather than an array.
+    image.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::D2Array),
+        ..default()
+    });
+    image
+}
+
+/// The range of pixels of an image of the given size covered by a texel of the mask texture.
+fn source_range(texel: u32, size: u32) -> (u32, u32) {
+    let start = (texel * size / MASK_RESOLUTION).min(size - 1);
+    let end = ((texel + 1) * size / MASK_RESOLUTION).clamp(start + 1, size);
+    (start, end)
+}
+
+/// The size of each pixel and the offset of the alpha channel within it, in bytes.
+fn alpha_channel(format: TextureFormat) -> Option<(usize, usize)> {
+    match format {
+        TextureFormat::Rgba8Unorm
+        | TextureFormat::Rgba8UnormSrgb
+        | TextureFormat::Bgra8Unorm
+        | TextureFormat::Bgra8UnormSrgb => Some((4, 3)),
+        TextureFormat::R8Unorm => Some((1, 0)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(size: UVec2, format: TextureFormat, data: Vec<u8>) -> Image {
+        Image::new(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            format,
+            RenderAssetUsages::MAIN_WORLD,
+        )
+    }
+
+    #[test]
+    fn source_range_covers_every_pixel_once() {
+        for size in [1, 3, MASK_RESOLUTION, 1000] {
+            let mut next = 0;
+            for texel in 0..MASK_RESOLUTION {
+                let (start, end) = source_range(texel, size);
+                assert!(start < end && end <= size, "size {size}, texel {texel}");
+                if size >= MASK_RESOLUTION {
+                    assert_eq!(start, next, "size {size}, texel {texel}");
+                    next = end;
+                }
+            }
+            if size >= MASK_RESOLUTION {
+                assert_eq!(next, size);
+            }
+        }
+    }
+
+    #[test]
+    fn source_range_picks_the_nearest_pixel_of_small_images() {
+        assert_eq!(source_range(0, 2), (0, 1));
+        assert_eq!(source_range(MASK_RESOLUTION / 2 - 1, 2), (0, 1));
+        assert_eq!(source_range(MASK_RESOLUTION / 2, 2), (1, 2));
+        assert_eq!(source_range(MASK_RESOLUTION - 1, 2), (1, 2));
+    }
+
+    #[test]
+    fn mask_texture_averages_alpha() {
+        let mut images = Assets::<Image>::default();
+        // The left half is opaque and the right half transparent, stored in the alpha channel.
+        let data = [0, 0, 0, 255, 0, 0, 0, 0].repeat(MASK_RESOLUTION as usize * 2);
+        let id = images
+            .add(image(
+                UVec2::new(MASK_RESOLUTION * 2, 2),
+                TextureFormat::Rgba8Unorm,
+                data,
+            ))
+            .id();
+
+        let texture = build_mask_texture(&[id], Some(&images));
+        assert!(texture.data.iter().all(|&alpha| alpha == 127));
+    }
+
+    #[test]
+    fn mask_texture_skips_unreadable_images() {
+        let mut images = Assets::<Image>::default();
+        let mut short = image(UVec2::new(4, 4), TextureFormat::R8Unorm, vec![255; 16]);
+        short.data.truncate(8);
+        let unsupported = image(UVec2::new(1, 1), TextureFormat::Rgba32Float, vec![255; 16]);
+        let ids = [images.add(short).id(), images.add(unsupported).id()];
+
+        let texture = build_mask_texture(&ids, Some(&images));
+        assert_eq!(texture.texture_descriptor.size.depth_or_array_layers, 2);
+        assert!(texture.data.iter().all(|&alpha| alpha == 0));
+    }
+}
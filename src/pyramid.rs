@@ -0,0 +1,327 @@
+use bevy::asset::load_internal_asset;
+use bevy::core_pipeline::fullscreen_vertex_shader::fullscreen_shader_vertex_state;
+use bevy::prelude::*;
+use bevy::render::camera::ExtractedCamera;
+use bevy::render::render_resource::binding_types::sampler;
+use bevy::render::render_resource::binding_types::texture_2d;
+use bevy::render::render_resource::AddressMode;
+use bevy::render::render_resource::BindGroupEntries;
+use bevy::render::render_resource::BindGroupLayout;
+use bevy::render::render_resource::BindGroupLayoutEntries;
+use bevy::render::render_resource::CachedRenderPipelineId;
+use bevy::render::render_resource::ColorTargetState;
+use bevy::render::render_resource::ColorWrites;
+use bevy::render::render_resource::Extent3d;
+use bevy::render::render_resource::FilterMode;
+use bevy::render::render_resource::FragmentState;
+use bevy::render::render_resource::MultisampleState;
+use bevy::render::render_resource::Operations;
+use bevy::render::render_resource::PipelineCache;
+use bevy::render::render_resource::PrimitiveState;
+use bevy::render::render_resource::RenderPassColorAttachment;
+use bevy::render::render_resource::RenderPassDescriptor;
+use bevy::render::render_resource::RenderPipelineDescriptor;
+use bevy::render::render_resource::Sampler;
+use bevy::render::render_resource::SamplerBindingType;
+use bevy::render::render_resource::SamplerDescriptor;
+use bevy::render::render_resource::ShaderStages;
+use bevy::render::render_resource::SpecializedRenderPipeline;
+use bevy::render::render_resource::SpecializedRenderPipelines;
+use bevy::render::render_resource::TextureDescriptor;
+use bevy::render::render_resource::TextureDimension;
+use bevy::render::render_resource::TextureFormat;
+use bevy::render::render_resource::TextureSampleType;
+use bevy::render::render_resource::TextureUsages;
+use bevy::render::render_resource::TextureView;
+use bevy::render::render_resource::TextureViewDescriptor;
+use bevy::render::renderer::RenderContext;
+use bevy::render::renderer::RenderDevice;
+use bevy::render::texture::CachedTexture;
+use bevy::render::texture::TextureCache;
+use bevy::render::Render;
+use bevy::render::RenderApp;
+use bevy::render::RenderSet;
+
+use crate::BlurAlgorithm;
+use crate::BlurRegionsCamera;
+
+const PYRAMID_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(150283746529384710293847561029384756102);
+
+/// The format of the levels of the blur pyramid, which has the range to hold HDR colors.
+pub(crate) const PYRAMID_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// The max number of levels of the blur pyramid, enough for a circle of confusion of 512 pixels.
+const MAX_PYRAMID_LEVELS: u32 = 8;
+
+/// Renders a blur pyramid for each camera that uses [`BlurAlgorithm::DualKawase`] or
+/// [`BlurAlgorithm::MipChain`]. Each level of the pyramid halves the resolution of the level
+/// before it.
+///
+/// The blur regions shader then upsamples the levels back up from the smallest one, with a pass
+/// per level that keeps the downsampled level where it is blurrier than the circle of confusion of
+/// the pixel, so that each pixel ends up blurred by the level matching its circle of confusion.
+pub struct BlurPyramidPlugin;
+
+impl Plugin for BlurPyramidPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, PYRAMID_SHADER_HANDLE, "pyramid.wgsl", Shader::from_wgsl);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<SpecializedRenderPipelines<BlurPyramidPipeline>>()
+            .add_systems(Render, prepare_blur_pyramids.in_set(RenderSet::PrepareResources));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        let render_device = render_app.world().resource::<RenderDevice>().clone();
+        render_app.insert_resource(BlurPyramidPipeline::new(&render_device));
+    }
+}
+
+/// The levels of the blur pyramid of a camera, starting at half the resolution of the camera.
+#[derive(Component)]
+pub struct BlurPyramid {
+    texture: CachedTexture,
+    mip_views: Vec<TextureView>,
+    /// The levels of the pyramid once they are upsampled, except the smallest level, which is
+    /// upsampled as it is.
+    upsampled: Option<CachedTexture>,
+    upsampled_mip_views: Vec<TextureView>,
+    pipeline: CachedRenderPipelineId,
+}
+
+impl BlurPyramid {
+    /// Downsamples the source texture into each level of the pyramid. Returns `false` without
+    /// rendering anything if the pipeline isn't ready yet.
+    pub(crate) fn render(&self, render_context: &mut RenderContext, source: &TextureView, world: &World) -> bool {
+        let pyramid_pipeline = world.resource::<BlurPyramidPipeline>();
+        let Some(pipeline) = world.resource::<PipelineCache>().get_render_pipeline(self.pipeline) else {
+            return false;
+        };
+
+        let mut source = source;
+        for destination in &self.mip_views {
+            let bind_group = render_context.render_device().create_bind_group(
+                "blur_pyramid_bind_group",
+                &pyramid_pipeline.layout,
+                &BindGroupEntries::sequential((source, &pyramid_pipeline.sampler)),
+            );
+
+            let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("blur pyramid downsample pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: destination,
+                    resolve_target: None,
+                    ops: Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+            source = destination;
+        }
+
+        true
+    }
+
+    /// The level and textures of each upsampling pass, from the smallest level of the pyramid to
+    /// the largest: the downsampled level, the upsampled level below it, and the level to upsample
+    /// into.
+    pub(crate) fn upsample_passes(&self) -> impl Iterator<Item = (u32, &TextureView, &TextureView, &TextureView)> {
+        (0..self.upsampled_mip_views.len()).rev().map(|mip| {
+            let below = self.upsampled_mip_views.get(mip + 1).unwrap_or(&self.mip_views[mip + 1]);
+            (
+                mip as u32 + 1,
+                &self.mip_views[mip],
+                below,
+                &self.upsampled_mip_views[mip],
+            )
+        })
+    }
+
+    /// The largest upsampled level, which the composite pass upsamples to the screen.
+    pub(crate) fn blurred_view(&self) -> &TextureView {
+        match &self.upsampled {
+            Some(upsampled) => &upsampled.default_view,
+            None => &self.texture.default_view,
+        }
+    }
+}
+
+/// The number of levels of the blur pyramid of a camera with the given size, which are needed to
+/// blur with the given circle of confusion. The blur regions shader blurs with level
+/// `log2(circle_of_confusion / 2)`, where level 0 is the unblurred screen.
+pub(crate) fn pyramid_levels(target_size: UVec2, circle_of_confusion: f32) -> u32 {
+    let size = (target_size / 2).max(UVec2::ONE);
+    ((circle_of_confusion * 0.5).max(1.0).log2().ceil() as u32)
+        .clamp(1, MAX_PYRAMID_LEVELS)
+        .min(size.min_element().ilog2() + 1)
+}
+
+fn prepare_blur_pyramids(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<BlurPyramidPipeline>>,
+    pipeline: Res<BlurPyramidPipeline>,
+    mut texture_cache: ResMut<TextureCache>,
+    views: Query<(Entity, &ExtractedCamera, &BlurRegionsCamera)>,
+) {
+    for (entity, camera, blur_regions) in &views {
+        if !matches!(
+            blur_regions.algorithm,
+            BlurAlgorithm::DualKawase | BlurAlgorithm::MipChain
+        ) {
+            continue;
+        }
+
+        let Some(target_size) = camera.physical_target_size else {
+            continue;
+        };
+
+        let size = (target_size / 2).max(UVec2::ONE);
+        let levels = pyramid_levels(target_size, blur_regions.max_circle_of_confusion());
+        let mut texture = |label, levels| {
+            let texture = texture_cache.get(
+                &render_device,
+                TextureDescriptor {
+                    label: Some(label),
+                    size: Extent3d {
+                        width: size.x,
+                        height: size.y,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: levels,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: PYRAMID_TEXTURE_FORMAT,
+                    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                },
+            );
+            let mip_views = (0..levels)
+                .map(|mip| {
+                    texture.texture.create_view(&TextureViewDescriptor {
+                        label: Some("blur_pyramid_mip_view"),
+                        base_mip_level: mip,
+                        mip_level_count: Some(1),
+                        ..default()
+                    })
+                })
+                .collect::<Vec<_>>();
+            (texture, mip_views)
+        };
+
+        let (pyramid_texture, mip_views) = texture("blur_pyramid_texture", levels);
+        let (upsampled, upsampled_mip_views) = if levels > 1 {
+            let (upsampled, upsampled_mip_views) = texture("blur_pyramid_upsampled_texture", levels - 1);
+            (Some(upsampled), upsampled_mip_views)
+        } else {
+            (None, Vec::new())
+        };
+
+        commands.entity(entity).insert(BlurPyramid {
+            texture: pyramid_texture,
+            mip_views,
+            upsampled,
+            upsampled_mip_views,
+            pipeline: pipelines.specialize(&pipeline_cache, &pipeline, blur_regions.algorithm),
+        });
+    }
+}
+
+#[derive(Resource)]
+pub struct BlurPyramidPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+impl BlurPyramidPipeline {
+    fn new(render_device: &RenderDevice) -> Self {
+        let layout = render_device.create_bind_group_layout(
+            "blur_pyramid_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    sampler(SamplerBindingType::Filtering),
+                ),
+            ),
+        );
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        Self { layout, sampler }
+    }
+}
+
+impl SpecializedRenderPipeline for BlurPyramidPipeline {
+    type Key = BlurAlgorithm;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: Some("blur_pyramid_pipeline".into()),
+            layout: vec![self.layout.clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            fragment: Some(FragmentState {
+                shader: PYRAMID_SHADER_HANDLE,
+                shader_defs: vec![],
+                entry_point: match key {
+                    BlurAlgorithm::DualKawase => "dual_kawase_downsample".into(),
+                    BlurAlgorithm::Gaussian | BlurAlgorithm::GaussianCompute | BlurAlgorithm::MipChain => {
+                        "mip_chain_downsample".into()
+                    }
+                },
+                targets: vec![Some(ColorTargetState {
+                    format: PYRAMID_TEXTURE_FORMAT,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET_SIZE: UVec2 = UVec2::new(1920, 1080);
+
+    #[test]
+    fn pyramid_levels_match_the_circle_of_confusion() {
+        assert_eq!(pyramid_levels(TARGET_SIZE, 0.0), 1);
+        assert_eq!(pyramid_levels(TARGET_SIZE, 4.0), 1);
+        assert_eq!(pyramid_levels(TARGET_SIZE, 8.0), 2);
+        assert_eq!(pyramid_levels(TARGET_SIZE, 100.0), 6);
+        assert_eq!(pyramid_levels(TARGET_SIZE, 10_000.0), MAX_PYRAMID_LEVELS);
+    }
+
+    #[test]
+    fn pyramid_levels_stop_at_one_texel() {
+        // The smallest level of a 16x16 target is 1x1 at level 4.
+        assert_eq!(pyramid_levels(UVec2::new(16, 16), 10_000.0), 4);
+        assert_eq!(pyramid_levels(UVec2::new(1920, 1), 10_000.0), 1);
+        assert_eq!(pyramid_levels(UVec2::ZERO, 10_000.0), 1);
+    }
+}
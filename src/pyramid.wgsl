@@ -0,0 +1,52 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var source_texture: texture_2d<f32>;
+@group(0) @binding(1) var source_sampler: sampler;
+
+fn sample_source(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(source_texture, source_sampler, uv, 0.0).rgb;
+}
+
+// The downsampling filter of the Dual Kawase blur, which averages the center of the destination
+// texel with its four corners, each of which bilinearly filters four source texels.
+//
+// From: Bandwidth-Efficient Rendering, Marius Bjørge, SIGGRAPH 2015
+@fragment
+fn dual_kawase_downsample(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source_texture));
+    var sum = sample_source(in.uv) * 4.0;
+    sum += sample_source(in.uv + vec2(-texel.x, -texel.y));
+    sum += sample_source(in.uv + vec2(texel.x, -texel.y));
+    sum += sample_source(in.uv + vec2(-texel.x, texel.y));
+    sum += sample_source(in.uv + vec2(texel.x, texel.y));
+    return vec4(sum / 8.0, 1.0);
+}
+
+// A 13 tap downsampling filter made of overlapping 4x4 box filters, which covers a wider area than
+// the Dual Kawase filter and flickers less when the scene moves.
+//
+// From: Next Generation Post Processing in Call of Duty: Advanced Warfare, Jorge Jimenez,
+// SIGGRAPH 2014
+@fragment
+fn mip_chain_downsample(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(source_texture));
+    let a = sample_source(in.uv + texel * vec2(-2.0, -2.0));
+    let b = sample_source(in.uv + texel * vec2(0.0, -2.0));
+    let c = sample_source(in.uv + texel * vec2(2.0, -2.0));
+    let d = sample_source(in.uv + texel * vec2(-2.0, 0.0));
+    let e = sample_source(in.uv);
+    let f = sample_source(in.uv + texel * vec2(2.0, 0.0));
+    let g = sample_source(in.uv + texel * vec2(-2.0, 2.0));
+    let h = sample_source(in.uv + texel * vec2(0.0, 2.0));
+    let i = sample_source(in.uv + texel * vec2(2.0, 2.0));
+    let j = sample_source(in.uv + texel * vec2(-1.0, -1.0));
+    let k = sample_source(in.uv + texel * vec2(1.0, -1.0));
+    let l = sample_source(in.uv + texel * vec2(-1.0, 1.0));
+    let m = sample_source(in.uv + texel * vec2(1.0, 1.0));
+
+    var sum = e * 0.125;
+    sum += (a + c + g + i) * 0.03125;
+    sum += (b + d + f + h) * 0.0625;
+    sum += (j + k + l + m) * 0.125;
+    return vec4(sum, 1.0);
+}
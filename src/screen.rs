@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+
+use crate::BlurRegionMask;
+use crate::BlurRegionSettings;
+use crate::BlurRegionsCamera;
+use crate::BlurRegionsSystems;
+use crate::BlurTransition;
+
+pub struct BlurRegionsScreenPlugin;
+
+impl Plugin for BlurRegionsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, compute_screen_blur_regions.in_set(BlurRegionsSystems::Compute));
+    }
+}
+
+/// A blur region in screen space that is rendered for as long as the entity exists, without
+/// having to be submitted every frame like with [`BlurRegionsCamera::blur`].
+///
+/// Add a [`BlurRegionSettings`] component to the same entity to override the settings of the
+/// camera, or a [`BlurTransition`] to animate the blur.
+#[derive(Component, Debug, Clone)]
+pub struct ScreenBlurRegion {
+    /// The area to blur.
+    /// In physical pixels.
+    pub rect: Rect,
+    /// The radii of the top left, top right, bottom right and bottom left corners.
+    /// In physical pixels.
+    pub border_radii: Vec4,
+    /// The counterclockwise rotation of the blur region around the center of `rect`.
+    pub rotation: Rot2,
+    /// An image whose alpha channel is used as the shape of the blur region, stretched over `rect`.
+    pub mask: Option<BlurRegionMask>,
+    /// The camera that renders this blur region, or `None` for every camera with a
+    /// [`BlurRegionsCamera`].
+    pub camera: Option<Entity>,
+}
+
+impl ScreenBlurRegion {
+    pub fn new(rect: Rect) -> Self {
+        ScreenBlurRegion {
+            rect,
+            border_radii: Vec4::ZERO,
+            rotation: Rot2::IDENTITY,
+            mask: None,
+            camera: None,
+        }
+    }
+
+    pub fn with_border_radii(mut self, border_radii: Vec4) -> Self {
+        self.border_radii = border_radii;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Rot2) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_mask(mut self, mask: impl Into<BlurRegionMask>) -> Self {
+        self.mask = Some(mask.into());
+        self
+    }
+
+    pub fn with_camera(mut self, camera: Entity) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+}
+
+fn compute_screen_blur_regions(
+    regions: Query<(&ScreenBlurRegion, Option<&BlurRegionSettings>, Option<&BlurTransition>)>,
+    mut blur_regions_cameras: Query<(Entity, &mut BlurRegionsCamera)>,
+) {
+    for (camera, mut blur_regions) in &mut blur_regions_cameras {
+        for (region, settings, transition) in &regions {
+            if region.camera.is_some_and(|target| target != camera) {
+                continue;
+            }
+
+            let mut settings = settings.copied().unwrap_or_else(|| blur_regions.settings());
+            if let Some(transition) = transition {
+                settings = transition.apply(settings);
+            }
+            blur_regions.rotated_masked_blur_with(
+                region.rect,
+                region.border_radii,
+                region.rotation,
+                region.mask.as_ref(),
+                settings,
+            );
+        }
+    }
+}
@@ -4,27 +4,40 @@ use bevy::core_pipeline::core_2d::graph::Node2d;
 use bevy::core_pipeline::core_3d::graph::Core3d;
 use bevy::core_pipeline::core_3d::graph::Node3d;
 use bevy::core_pipeline::fullscreen_vertex_shader::fullscreen_shader_vertex_state;
+use bevy::ecs::entity::EntityHashMap;
 use bevy::ecs::query::QueryItem;
 use bevy::ecs::system::lifetimeless::Read;
 use bevy::prelude::*;
-use bevy::render::extract_component::ComponentUniforms;
+use bevy::render::camera::ExtractedCamera;
 use bevy::render::extract_component::ExtractComponentPlugin;
-use bevy::render::extract_component::UniformComponentPlugin;
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_graph::InternedRenderLabel;
+use bevy::render::render_graph::InternedRenderSubGraph;
 use bevy::render::render_graph::NodeRunError;
+use bevy::render::render_graph::RenderGraph;
 use bevy::render::render_graph::RenderGraphApp;
 use bevy::render::render_graph::RenderGraphContext;
 use bevy::render::render_graph::RenderLabel;
+use bevy::render::render_graph::RenderSubGraph;
 use bevy::render::render_graph::ViewNode;
 use bevy::render::render_graph::ViewNodeRunner;
+use bevy::render::render_phase::TrackedRenderPass;
 use bevy::render::render_resource::binding_types::sampler;
+use bevy::render::render_resource::binding_types::storage_buffer_read_only;
 use bevy::render::render_resource::binding_types::texture_2d;
+use bevy::render::render_resource::binding_types::texture_2d_array;
 use bevy::render::render_resource::binding_types::uniform_buffer;
+use bevy::render::render_resource::AddressMode;
 use bevy::render::render_resource::BindGroupEntries;
+use bevy::render::render_resource::BindGroupEntry;
 use bevy::render::render_resource::BindGroupLayout;
 use bevy::render::render_resource::BindGroupLayoutEntries;
+use bevy::render::render_resource::BindingResource;
 use bevy::render::render_resource::CachedRenderPipelineId;
 use bevy::render::render_resource::ColorTargetState;
 use bevy::render::render_resource::ColorWrites;
+use bevy::render::render_resource::Extent3d;
+use bevy::render::render_resource::FilterMode;
 use bevy::render::render_resource::FragmentState;
 use bevy::render::render_resource::MultisampleState;
 use bevy::render::render_resource::Operations;
@@ -32,54 +45,85 @@ use bevy::render::render_resource::PipelineCache;
 use bevy::render::render_resource::PrimitiveState;
 use bevy::render::render_resource::RenderPassColorAttachment;
 use bevy::render::render_resource::RenderPassDescriptor;
+use bevy::render::render_resource::RenderPipeline;
 use bevy::render::render_resource::RenderPipelineDescriptor;
 use bevy::render::render_resource::Sampler;
 use bevy::render::render_resource::SamplerBindingType;
 use bevy::render::render_resource::SamplerDescriptor;
 use bevy::render::render_resource::ShaderDefVal;
 use bevy::render::render_resource::ShaderStages;
+use bevy::render::render_resource::ShaderType;
 use bevy::render::render_resource::SpecializedRenderPipeline;
 use bevy::render::render_resource::SpecializedRenderPipelines;
+use bevy::render::render_resource::StorageBuffer;
+use bevy::render::render_resource::TextureDescriptor;
+use bevy::render::render_resource::TextureDimension;
 use bevy::render::render_resource::TextureFormat;
 use bevy::render::render_resource::TextureSampleType;
+use bevy::render::render_resource::TextureUsages;
+use bevy::render::render_resource::TextureView;
+use bevy::render::render_resource::UniformBuffer;
 use bevy::render::renderer::RenderContext;
 use bevy::render::renderer::RenderDevice;
+use bevy::render::renderer::RenderQueue;
 use bevy::render::texture::BevyDefault;
+use bevy::render::texture::CachedTexture;
+use bevy::render::texture::FallbackImage;
+use bevy::render::texture::FallbackImageZero;
+use bevy::render::texture::GpuImage;
+use bevy::render::texture::TextureCache;
 use bevy::render::view::ExtractedView;
 use bevy::render::view::ViewTarget;
 use bevy::render::Render;
 use bevy::render::RenderApp;
 use bevy::render::RenderSet;
+#[cfg(feature = "bevy_ui")]
+use bevy::ui::graph::NodeUi;
 
+use crate::blur_mask::BlurMaskDraws;
+use crate::compute::BlurComputeTextures;
+use crate::compute::COMPUTE_WORKGROUP_SIZE;
+use crate::core::ComputedBlurRegion;
+use crate::core::BLUR_REGION_TILE_SIZE;
+use crate::core::UNIFORM_MAX_BLUR_REGIONS_COUNT;
+use crate::mask::BlurRegionMasks;
+use crate::pyramid::pyramid_levels;
+use crate::pyramid::BlurPyramid;
+use crate::pyramid::PYRAMID_TEXTURE_FORMAT;
+use crate::tilt_shift::GpuTiltShift;
+use crate::BlurAlgorithm;
 use crate::BlurRegionsCamera;
+use crate::BlurRenderMode;
 
-const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(271147050642476932735403127655134602927);
+/// The binding of the blur region tiles, which only exists on platforms with storage buffer support.
+const TILES_BINDING: u32 = 7;
 
-pub struct BlurRegionsShaderPlugin<const N: usize>;
+pub(crate) const SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(271147050642476932735403127655134602927);
 
-impl<const N: usize> Plugin for BlurRegionsShaderPlugin<N> {
+pub struct BlurRegionsShaderPlugin {
+    pub(crate) graph_placement: BlurRegionsGraphPlacement,
+}
+
+impl Plugin for BlurRegionsShaderPlugin {
     fn build(&self, app: &mut App) {
         load_internal_asset!(app, SHADER_HANDLE, "shader.wgsl", Shader::from_wgsl);
 
-        app.add_plugins((
-            ExtractComponentPlugin::<BlurRegionsCamera<N>>::default(),
-            UniformComponentPlugin::<BlurRegionsCamera<N>>::default(),
-        ));
+        app.add_plugins(ExtractComponentPlugin::<BlurRegionsCamera>::default());
 
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
         };
 
         render_app
-            .init_resource::<SpecializedRenderPipelines<BlurRegionsPipeline<N>>>()
+            .init_resource::<SpecializedRenderPipelines<BlurRegionsPipeline>>()
+            .init_resource::<BlurRegionsBuffers>()
             .add_systems(
                 Render,
-                (prepare_blur_regions_pipelines::<N>.in_set(RenderSet::Prepare),),
-            )
-            .add_render_graph_node::<ViewNodeRunner<BlurRegionsNode<N>>>(Core3d, BlurRegionsLabel)
-            .add_render_graph_edges(Core3d, (Node3d::DepthOfField, BlurRegionsLabel, Node3d::Tonemapping))
-            .add_render_graph_node::<ViewNodeRunner<BlurRegionsNode<N>>>(Core2d, BlurRegionsLabel)
-            .add_render_graph_edges(Core2d, (Node2d::Bloom, BlurRegionsLabel, Node2d::Tonemapping));
+                (
+                    prepare_blur_regions_pipelines.in_set(RenderSet::Prepare),
+                    prepare_blur_regions_buffers.in_set(RenderSet::PrepareResources),
+                ),
+            );
     }
 
     fn finish(&self, app: &mut App) {
@@ -88,50 +132,344 @@ impl<const N: usize> Plugin for BlurRegionsShaderPlugin<N> {
         };
 
         let render_device = render_app.world().resource::<RenderDevice>().clone();
-        render_app.insert_resource(BlurRegionsPipeline::<N>::new(&render_device));
+        render_app.insert_resource(BlurRegionsPipeline::new(&render_device));
+
+        // The node is added once every plugin is built, so that the nodes around it exist no matter
+        // the order the plugins were added in.
+        for edges in self.graph_placement.edges() {
+            render_app.add_render_graph_node::<ViewNodeRunner<BlurRegionsNode>>(edges.graph, BlurRegionsLabel);
+            for (output, input) in edges.node_edges(render_app.world()) {
+                render_app.add_render_graph_edge(edges.graph, output, input);
+            }
+        }
     }
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
 pub struct BlurRegionsLabel;
 
+/// Where the blur regions node runs in the render graph of each camera, set with
+/// [`BlurRegionsPlugin::graph_placement`](crate::BlurRegionsPlugin::graph_placement).
+///
+/// Nodes before tonemapping blur linear HDR color, so bright areas stay bright once blurred, while
+/// nodes after tonemapping blur the display-referred color that ends up on the screen.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BlurRegionsGraphPlacement {
+    /// After the main pass, motion blur and TAA, and before bloom, so that the bloom of bright
+    /// areas isn't blurred away.
+    BeforeBloom,
+    /// After bloom and depth of field, and before tonemapping.
+    #[default]
+    BeforeTonemapping,
+    /// After tonemapping, and before FXAA, SMAA and contrast adaptive sharpening, so that blurred
+    /// areas match the colors of the screen exactly and their edges are still anti-aliased.
+    AfterTonemapping,
+    /// After the Bevy UI pass, so that UI is blurred along with the rest of the screen.
+    #[cfg(feature = "bevy_ui")]
+    AfterUi,
+    /// Splits the Bevy UI at the lowest blurred Bevy UI node, and runs the blur in between, so that
+    /// blurred nodes blur the UI drawn below them, such as a modal dialog over a HUD.
+    ///
+    /// The UI below the lowest blurred node is drawn first, then everything is blurred, and then
+    /// the rest of the UI is drawn on top. Blurred nodes above the lowest one are drawn after the
+    /// blur too, so they don't blur each other.
+    #[cfg(feature = "bevy_ui")]
+    UiBackdrop,
+    /// Between two nodes of the given render graph, such as a custom render graph that isn't
+    /// [`Core2d`] or [`Core3d`].
+    Custom {
+        graph: InternedRenderSubGraph,
+        after: InternedRenderLabel,
+        before: InternedRenderLabel,
+    },
+}
+
+impl BlurRegionsGraphPlacement {
+    pub fn custom(graph: impl RenderSubGraph, after: impl RenderLabel, before: impl RenderLabel) -> Self {
+        BlurRegionsGraphPlacement::Custom {
+            graph: graph.intern(),
+            after: after.intern(),
+            before: before.intern(),
+        }
+    }
+
+    /// The render graphs that the blur regions node is added to, along with the nodes that it runs
+    /// between in each of them.
+    pub(crate) fn edges(&self) -> Vec<BlurRegionsGraphEdges> {
+        match self {
+            BlurRegionsGraphPlacement::BeforeBloom => vec![
+                BlurRegionsGraphEdges::new(Core3d, Node3d::EndMainPass, Node3d::Bloom)
+                    .also_after([Node3d::MotionBlur, Node3d::Taa]),
+                BlurRegionsGraphEdges::new(Core2d, Node2d::EndMainPass, Node2d::Bloom),
+            ],
+            BlurRegionsGraphPlacement::BeforeTonemapping => vec![
+                BlurRegionsGraphEdges::new(Core3d, Node3d::DepthOfField, Node3d::Tonemapping),
+                BlurRegionsGraphEdges::new(Core2d, Node2d::Bloom, Node2d::Tonemapping),
+            ],
+            BlurRegionsGraphPlacement::AfterTonemapping => vec![
+                BlurRegionsGraphEdges::new(Core3d, Node3d::Tonemapping, Node3d::EndMainPassPostProcessing)
+                    .also_before([Node3d::Fxaa, Node3d::Smaa, Node3d::ContrastAdaptiveSharpening]),
+                BlurRegionsGraphEdges::new(Core2d, Node2d::Tonemapping, Node2d::EndMainPassPostProcessing)
+                    .also_before([Node2d::Fxaa, Node2d::Smaa, Node2d::ContrastAdaptiveSharpening]),
+            ],
+            #[cfg(feature = "bevy_ui")]
+            BlurRegionsGraphPlacement::AfterUi | BlurRegionsGraphPlacement::UiBackdrop => vec![
+                BlurRegionsGraphEdges::new(Core3d, NodeUi::UiPass, Node3d::Upscaling),
+                BlurRegionsGraphEdges::new(Core2d, NodeUi::UiPass, Node2d::Upscaling),
+            ],
+            BlurRegionsGraphPlacement::Custom { graph, after, before } => {
+                vec![BlurRegionsGraphEdges::new(*graph, *after, *before)]
+            }
+        }
+    }
+}
+
+/// The nodes that the blur regions node runs between in a render graph.
+pub(crate) struct BlurRegionsGraphEdges {
+    pub(crate) graph: InternedRenderSubGraph,
+    after: InternedRenderLabel,
+    before: InternedRenderLabel,
+    /// Nodes that also write to the view target between `after` and `before`, whose order
+    /// relative to the blur regions node would otherwise be undefined. They only exist when their
+    /// plugins are added, such as TAA.
+    also_after: Vec<InternedRenderLabel>,
+    also_before: Vec<InternedRenderLabel>,
+}
+
+impl BlurRegionsGraphEdges {
+    fn new(graph: impl RenderSubGraph, after: impl RenderLabel, before: impl RenderLabel) -> Self {
+        BlurRegionsGraphEdges {
+            graph: graph.intern(),
+            after: after.intern(),
+            before: before.intern(),
+            also_after: Vec::new(),
+            also_before: Vec::new(),
+        }
+    }
+
+    fn also_after(mut self, nodes: impl IntoIterator<Item = impl RenderLabel>) -> Self {
+        self.also_after.extend(nodes.into_iter().map(|node| node.intern()));
+        self
+    }
+
+    fn also_before(mut self, nodes: impl IntoIterator<Item = impl RenderLabel>) -> Self {
+        self.also_before.extend(nodes.into_iter().map(|node| node.intern()));
+        self
+    }
+
+    /// The edges between the blur regions node and the nodes around it, skipping the nodes that
+    /// aren't in the render graph.
+    pub(crate) fn node_edges(&self, world: &World) -> Vec<(InternedRenderLabel, InternedRenderLabel)> {
+        let Some(graph) = world.resource::<RenderGraph>().get_sub_graph(self.graph) else {
+            return Vec::new();
+        };
+
+        let exists = |node: &InternedRenderLabel| graph.get_node_state(*node).is_ok();
+        for node in [self.after, self.before] {
+            if !exists(&node) {
+                warn!(
+                    "The blur regions node can't be placed relative to {node:?}, which isn't in the render graph {:?}",
+                    self.graph
+                );
+            }
+        }
+
+        let blur_regions = BlurRegionsLabel.intern();
+        [self.after]
+            .iter()
+            .chain(&self.also_after)
+            .filter(|node| exists(node))
+            .map(|&node| (node, blur_regions))
+            .chain(
+                [self.before]
+                    .iter()
+                    .chain(&self.also_before)
+                    .filter(|node| exists(node))
+                    .map(|&node| (blur_regions, node)),
+            )
+            .collect()
+    }
+}
+
 #[derive(Default)]
-pub struct BlurRegionsNode<const N: usize>;
+pub struct BlurRegionsNode;
 
-impl<const N: usize> ViewNode for BlurRegionsNode<N> {
-    type ViewQuery = (Read<ViewTarget>, Read<BlurRegionsPasses>);
+impl ViewNode for BlurRegionsNode {
+    type ViewQuery = (
+        Read<ViewTarget>,
+        Read<BlurRegionsCamera>,
+        Read<BlurRegionsPasses>,
+        Option<Read<BlurMaskDraws>>,
+        Option<Read<BlurPyramid>>,
+        Option<Read<BlurComputeTextures>>,
+    );
 
     fn run(
         &self,
-        _graph: &mut RenderGraphContext,
+        graph: &mut RenderGraphContext,
         render_context: &mut RenderContext,
-        (view_target, passes): QueryItem<Self::ViewQuery>,
+        (view_target, blur_regions, passes, blur_mask_draws, pyramid, compute): QueryItem<Self::ViewQuery>,
         world: &World,
     ) -> Result<(), NodeRunError> {
-        let blur_regions_pipeline = world.resource::<BlurRegionsPipeline<N>>();
+        // Skip the fullscreen passes entirely when there is nothing to blur, such as in an idle menu.
+        if !blur_regions.is_active() && blur_mask_draws.is_none() {
+            return Ok(());
+        }
+
+        let blur_regions_pipeline = world.resource::<BlurRegionsPipeline>();
         let pipeline_cache = world.resource::<PipelineCache>();
 
-        let blur_regions = world.resource::<ComponentUniforms<BlurRegionsCamera<N>>>().uniforms();
-        let Some(blur_regions_binding) = blur_regions.binding() else {
+        let buffers = world.resource::<BlurRegionsBuffers>();
+        let Some(blur_regions_binding) = buffers.views.get(&graph.view_entity()).and_then(|b| b.binding()) else {
+            return Ok(());
+        };
+        // Blur regions are only binned into tiles on platforms with storage buffer support.
+        let tiles_binding = buffers.tiles.get(&graph.view_entity()).and_then(|b| b.binding());
+        if blur_regions_pipeline.storage_buffers_supported && tiles_binding.is_none() {
             return Ok(());
+        }
+
+        // The mask texture may not have been uploaded yet, in which case masks are ignored.
+        let masks = world.resource::<BlurRegionMasks>();
+        let mask_texture = match world.resource::<RenderAssets<GpuImage>>().get(&masks.image) {
+            Some(mask_image) => &mask_image.texture_view,
+            None => &world.resource::<FallbackImage>().d2_array.texture_view,
+        };
+
+        // Without any blur masks, nothing has been drawn into a silhouette texture for this view.
+        let silhouette_texture = match blur_mask_draws {
+            Some(blur_mask_draws) => &blur_mask_draws.texture.default_view,
+            None => &world.resource::<FallbackImageZero>().texture_view,
         };
 
-        for pass in &passes.0 {
+        let render_device = render_context.render_device().clone();
+        let create_bind_group = |label: &'static str, source: &TextureView, prepass_texture: &TextureView| {
+            let mut entries = BindGroupEntries::sequential((
+                source,
+                &blur_regions_pipeline.sampler,
+                blur_regions_binding.clone(),
+                mask_texture,
+                &blur_regions_pipeline.mask_sampler,
+                silhouette_texture,
+                prepass_texture,
+            ))
+            .to_vec();
+            if let Some(tiles_binding) = &tiles_binding {
+                entries.push(BindGroupEntry {
+                    binding: TILES_BINDING,
+                    resource: tiles_binding.clone(),
+                });
+            }
+            render_device.create_bind_group(label, &blur_regions_pipeline.layout, &entries)
+        };
+
+        // The silhouettes of blur masks may cover any part of the screen, so they can't be scissored.
+        let scissor = passes.scissor.as_ref().filter(|_| blur_mask_draws.is_none());
+        let scissor = match scissor {
+            Some(scissor) => match pipeline_cache.get_render_pipeline(scissor.copy_pipeline) {
+                Some(copy_pipeline) => Some((scissor.rects.as_slice(), copy_pipeline)),
+                None => return Ok(()),
+            },
+            None => None,
+        };
+
+        // The vertical pass of the Gaussian blur blurs the result of the horizontal pass, the passes
+        // of the Dual Kawase and mip chain blurs sample the blur pyramid, and the pass of the compute
+        // Gaussian blur mixes in the result of the compute passes, all of which must be rendered
+        // from the screen first.
+        let fallback_texture = &world.resource::<FallbackImage>().d2.texture_view;
+        let prepass_texture = match &passes.prepass {
+            BlurPrepass::Horizontal { pipeline, texture } => {
+                let Some(horizontal_pipeline) = pipeline_cache.get_render_pipeline(*pipeline) else {
+                    return Ok(());
+                };
+
+                let bind_group = create_bind_group(
+                    "blur regions bind group (horizontal pass)",
+                    view_target.main_texture_view(),
+                    fallback_texture,
+                );
+
+                let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                    label: Some("blur regions (horizontal pass)"),
+                    color_attachments: &[Some(RenderPassColorAttachment {
+                        view: &texture.default_view,
+                        resolve_target: None,
+                        ops: Operations::default(),
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+
+                render_pass.set_bind_group(0, &bind_group, &[]);
+                draw_blur_pass(&mut render_pass, horizontal_pipeline, scissor);
+
+                &texture.default_view
+            }
+            BlurPrepass::Pyramid { upsample_pipelines } => {
+                let Some(pyramid) = pyramid else {
+                    return Ok(());
+                };
+                let Some(upsample_pipelines) = upsample_pipelines
+                    .iter()
+                    .map(|id| pipeline_cache.get_render_pipeline(*id))
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    return Ok(());
+                };
+                if !pyramid.render(render_context, view_target.main_texture_view(), world) {
+                    return Ok(());
+                }
+
+                for (level, source, below, destination) in pyramid.upsample_passes() {
+                    let Some(upsample_pipeline) = upsample_pipelines.get(level as usize - 1) else {
+                        return Ok(());
+                    };
+
+                    let bind_group = create_bind_group("blur regions bind group (upsample pass)", source, below);
+
+                    let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                        label: Some("blur regions (upsample pass)"),
+                        color_attachments: &[Some(RenderPassColorAttachment {
+                            view: destination,
+                            resolve_target: None,
+                            ops: Operations::default(),
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+
+                    render_pass.set_render_pipeline(upsample_pipeline);
+                    render_pass.set_bind_group(0, &bind_group, &[]);
+                    render_pass.draw(0..3, 0..1);
+                }
+
+                pyramid.blurred_view()
+            }
+            BlurPrepass::Compute => {
+                let Some(compute) = compute else {
+                    return Ok(());
+                };
+                let source = view_target.main_texture_view();
+                let bind_group =
+                    create_bind_group("blur regions bind group (compute passes)", source, fallback_texture);
+                if !compute.render(render_context, &bind_group, source, world) {
+                    return Ok(());
+                }
+                &compute.vertical.default_view
+            }
+        };
+
+        for pass in &passes.passes {
             let Some(pass_pipeline) = pipeline_cache.get_render_pipeline(pass.pipeline) else {
                 return Ok(());
             };
 
             let post_process = view_target.post_process_write();
 
-            let bind_group = render_context.render_device().create_bind_group(
-                pass.bind_group_label,
-                &blur_regions_pipeline.layout,
-                &BindGroupEntries::sequential((
-                    post_process.source,
-                    &blur_regions_pipeline.sampler,
-                    blur_regions_binding.clone(),
-                )),
-            );
+            let bind_group = create_bind_group(pass.bind_group_label, post_process.source, prepass_texture);
 
             let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
                 label: Some(pass.pass_label),
@@ -145,42 +483,295 @@ impl<const N: usize> ViewNode for BlurRegionsNode<N> {
                 occlusion_query_set: None,
             });
 
-            render_pass.set_render_pipeline(pass_pipeline);
             render_pass.set_bind_group(0, &bind_group, &[]);
-            render_pass.draw(0..3, 0..1);
+            draw_blur_pass(&mut render_pass, pass_pipeline, scissor);
         }
 
         Ok(())
     }
 }
 
+/// Draws a blur pass over the whole screen, or with a scissor, copies the whole screen once and then
+/// only blurs the given rects.
+fn draw_blur_pass<'w>(
+    render_pass: &mut TrackedRenderPass<'w>,
+    pipeline: &'w RenderPipeline,
+    scissor: Option<(&[URect], &'w RenderPipeline)>,
+) {
+    let Some((rects, copy_pipeline)) = scissor else {
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.draw(0..3, 0..1);
+        return;
+    };
+
+    render_pass.set_render_pipeline(copy_pipeline);
+    render_pass.draw(0..3, 0..1);
+    render_pass.set_render_pipeline(pipeline);
+    for rect in rects {
+        render_pass.set_scissor_rect(rect.min.x, rect.min.y, rect.width(), rect.height());
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+/// The blur regions of a single camera, laid out for a storage buffer.
+#[derive(Default, ShaderType)]
+struct GpuBlurRegions {
+    count: u32,
+    defaults: ComputedBlurRegion,
+    tilt_shift: GpuTiltShift,
+    #[size(runtime)]
+    regions: Vec<ComputedBlurRegion>,
+}
+
+/// The blur regions of a single camera, laid out for a uniform buffer on platforms without
+/// storage buffer support.
+#[derive(ShaderType)]
+struct GpuBlurRegionsArray {
+    count: u32,
+    defaults: ComputedBlurRegion,
+    tilt_shift: GpuTiltShift,
+    regions: [ComputedBlurRegion; UNIFORM_MAX_BLUR_REGIONS_COUNT],
+}
+
+impl Default for GpuBlurRegionsArray {
+    fn default() -> Self {
+        GpuBlurRegionsArray {
+            count: 0,
+            defaults: ComputedBlurRegion::OFFSCREEN,
+            tilt_shift: GpuTiltShift::default(),
+            regions: std::array::from_fn(|_| ComputedBlurRegion::OFFSCREEN),
+        }
+    }
+}
+
+/// The blur regions of a single camera binned into tiles of the screen, laid out as described by
+/// `BlurRegionsCamera::bin_regions`. Only used on platforms with storage buffer support.
+#[derive(Default, ShaderType)]
+struct GpuBlurRegionTiles {
+    tile_size: u32,
+    /// The number of columns of tiles, or zero if the blur regions aren't binned.
+    columns: u32,
+    /// The number of rows of tiles, or zero if the blur regions aren't binned.
+    rows: u32,
+    #[size(runtime)]
+    data: Vec<u32>,
+}
+
+enum BlurRegionsBuffer {
+    Storage(Box<StorageBuffer<GpuBlurRegions>>),
+    Uniform(Box<UniformBuffer<GpuBlurRegionsArray>>),
+}
+
+impl BlurRegionsBuffer {
+    fn new(storage_buffers_supported: bool) -> Self {
+        if storage_buffers_supported {
+            let mut buffer = StorageBuffer::<GpuBlurRegions>::default();
+            buffer.set_label(Some("blur_regions_storage_buffer"));
+            BlurRegionsBuffer::Storage(Box::new(buffer))
+        } else {
+            let mut buffer = UniformBuffer::<GpuBlurRegionsArray>::default();
+            buffer.set_label(Some("blur_regions_uniform_buffer"));
+            BlurRegionsBuffer::Uniform(Box::new(buffer))
+        }
+    }
+
+    fn set<'a>(
+        &mut self,
+        defaults: ComputedBlurRegion,
+        tilt_shift: GpuTiltShift,
+        regions: impl ExactSizeIterator<Item = &'a ComputedBlurRegion>,
+    ) {
+        match self {
+            BlurRegionsBuffer::Storage(buffer) => {
+                let value = buffer.get_mut();
+                value.count = regions.len() as u32;
+                value.defaults = defaults;
+                value.tilt_shift = tilt_shift;
+                value.regions.clear();
+                value.regions.extend(regions.cloned());
+                // The binding expects room for at least one region, even when there are none.
+                if value.regions.is_empty() {
+                    value.regions.push(ComputedBlurRegion::OFFSCREEN);
+                }
+            }
+            BlurRegionsBuffer::Uniform(buffer) => {
+                // The overflow policy of the camera has already limited the blur regions to what
+                // fits in the array.
+                let value = buffer.get_mut();
+                value.count = 0;
+                value.defaults = defaults;
+                value.tilt_shift = tilt_shift;
+                for (slot, region) in value.regions.iter_mut().zip(regions) {
+                    *slot = region.clone();
+                    value.count += 1;
+                }
+            }
+        }
+    }
+
+    fn write_buffer(&mut self, render_device: &RenderDevice, render_queue: &RenderQueue) {
+        match self {
+            BlurRegionsBuffer::Storage(buffer) => buffer.write_buffer(render_device, render_queue),
+            BlurRegionsBuffer::Uniform(buffer) => buffer.write_buffer(render_device, render_queue),
+        }
+    }
+
+    fn binding(&self) -> Option<BindingResource<'_>> {
+        match self {
+            BlurRegionsBuffer::Storage(buffer) => buffer.binding(),
+            BlurRegionsBuffer::Uniform(buffer) => buffer.binding(),
+        }
+    }
+}
+
+/// The GPU buffers holding the blur regions of each camera. Buffers are kept between frames so
+/// that they only need to be reallocated when a camera's blur regions outgrow them.
+#[derive(Resource, Default)]
+struct BlurRegionsBuffers {
+    views: EntityHashMap<BlurRegionsBuffer>,
+    tiles: EntityHashMap<StorageBuffer<GpuBlurRegionTiles>>,
+}
+
+fn prepare_blur_regions_buffers(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    pipeline: Res<BlurRegionsPipeline>,
+    mut buffers: ResMut<BlurRegionsBuffers>,
+    views: Query<(Entity, &BlurRegionsCamera, &ExtractedView, &ExtractedCamera)>,
+) {
+    buffers.views.retain(|entity, _| views.contains(*entity));
+    buffers.tiles.retain(|entity, _| views.contains(*entity));
+
+    for (entity, blur_regions, view, camera) in &views {
+        let buffer =
+            buffers.views.entry(entity).or_insert_with(|| BlurRegionsBuffer::new(pipeline.storage_buffers_supported));
+        let tilt_shift = blur_regions.tilt_shift.map(|tilt_shift| tilt_shift.to_gpu(view.viewport)).unwrap_or_default();
+        buffer.set(blur_regions.default_region(), tilt_shift, blur_regions.regions());
+        buffer.write_buffer(&render_device, &render_queue);
+
+        if !pipeline.storage_buffers_supported {
+            continue;
+        }
+
+        let tiles = buffers.tiles.entry(entity).or_insert_with(|| {
+            let mut buffer = StorageBuffer::<GpuBlurRegionTiles>::default();
+            buffer.set_label(Some("blur_region_tiles_storage_buffer"));
+            buffer
+        });
+        let value = tiles.get_mut();
+        value.tile_size = BLUR_REGION_TILE_SIZE;
+        let grid = match camera.physical_target_size {
+            Some(target_size) => blur_regions.bin_regions(target_size, &mut value.data),
+            None => {
+                value.data.clear();
+                UVec2::ZERO
+            }
+        };
+        value.columns = grid.x;
+        value.rows = grid.y;
+        // The binding expects room for at least one entry, even when the blur regions aren't binned.
+        if value.data.is_empty() {
+            value.data.push(0);
+        }
+        tiles.write_buffer(&render_device, &render_queue);
+    }
+}
+
 #[derive(Resource)]
-pub struct BlurRegionsPipeline<const N: usize> {
-    layout: BindGroupLayout,
+pub struct BlurRegionsPipeline {
+    pub(crate) layout: BindGroupLayout,
     sampler: Sampler,
+    mask_sampler: Sampler,
+    storage_buffers_supported: bool,
+    /// Whether the compute passes of [`BlurAlgorithm::GaussianCompute`] can run, in which case
+    /// they share the bind group layout of this pipeline.
+    pub(crate) compute_supported: bool,
 }
 
-impl<const N: usize> BlurRegionsPipeline<N> {
+impl BlurRegionsPipeline {
     fn new(render_device: &RenderDevice) -> Self {
-        let layout = render_device.create_bind_group_layout(
-            "blur_regions_bind_group_layout",
-            &BindGroupLayoutEntries::sequential(
-                ShaderStages::FRAGMENT,
-                (
-                    texture_2d(TextureSampleType::Float { filterable: true }),
-                    sampler(SamplerBindingType::Filtering),
-                    uniform_buffer::<BlurRegionsCamera<N>>(false),
-                ),
+        let limits = render_device.limits();
+        let storage_buffers_supported = limits.max_storage_buffers_per_shader_stage > 0;
+        let compute_supported = storage_buffers_supported
+            && limits.max_storage_textures_per_shader_stage > 0
+            && limits.max_compute_invocations_per_workgroup >= COMPUTE_WORKGROUP_SIZE
+            && limits.max_compute_workgroup_size_x >= COMPUTE_WORKGROUP_SIZE;
+        let visibility = if compute_supported {
+            ShaderStages::FRAGMENT | ShaderStages::COMPUTE
+        } else {
+            ShaderStages::FRAGMENT
+        };
+
+        let mut entries = BindGroupLayoutEntries::sequential(
+            visibility,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+                if storage_buffers_supported {
+                    storage_buffer_read_only::<GpuBlurRegions>(false)
+                } else {
+                    uniform_buffer::<GpuBlurRegionsArray>(false)
+                },
+                texture_2d_array(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                texture_2d(TextureSampleType::Float { filterable: true }),
             ),
-        );
+        )
+        .to_vec();
+        if storage_buffers_supported {
+            entries.push(storage_buffer_read_only::<GpuBlurRegionTiles>(false).build(TILES_BINDING, visibility));
+        }
+        let layout = render_device.create_bind_group_layout("blur_regions_bind_group_layout", &entries);
         let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+        let mask_sampler = render_device.create_sampler(&SamplerDescriptor {
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
 
-        Self { layout, sampler }
+        Self {
+            layout,
+            sampler,
+            mask_sampler,
+            storage_buffers_supported,
+            compute_supported,
+        }
     }
 }
 
 #[derive(Component)]
-pub struct BlurRegionsPasses([BlurRegionsPass; 2]);
+pub struct BlurRegionsPasses {
+    passes: Vec<BlurRegionsPass>,
+    /// What the passes need rendered before they run.
+    prepass: BlurPrepass,
+    /// With [`BlurRenderMode::Scissored`], each pass copies the screen and then only blurs the
+    /// bounds of the blur regions, unless something covers the whole screen.
+    scissor: Option<BlurRegionsScissor>,
+}
+
+enum BlurPrepass {
+    /// The vertical pass of the Gaussian blur blurs the result of its horizontal pass, which is
+    /// rendered into `texture` so that the vertical pass can still mix the blur with the screen.
+    Horizontal {
+        pipeline: CachedRenderPipelineId,
+        texture: CachedTexture,
+    },
+    /// The passes sample a [`BlurPyramid`], once it is upsampled with a pipeline for each of its
+    /// levels but the smallest.
+    Pyramid {
+        upsample_pipelines: Vec<CachedRenderPipelineId>,
+    },
+    /// The passes mix in the result of the compute passes of [`BlurComputeTextures`].
+    Compute,
+}
+
+pub struct BlurRegionsScissor {
+    copy_pipeline: CachedRenderPipelineId,
+    rects: Vec<URect>,
+}
 
 pub struct BlurRegionsPass {
     pass_label: &'static str,
@@ -198,50 +789,143 @@ pub struct BlurRegionsPipelineKey {
 enum BlurRegionsPassKey {
     Horizontal,
     Vertical,
+    Composite(BlurAlgorithm),
+    /// Upsamples the given level of the blur pyramid.
+    Upsample(BlurAlgorithm, u32),
+    Copy,
 }
 
-fn prepare_blur_regions_pipelines<const N: usize>(
+fn prepare_blur_regions_pipelines(
     mut commands: Commands,
     pipeline_cache: Res<PipelineCache>,
-    mut pipelines: ResMut<SpecializedRenderPipelines<BlurRegionsPipeline<N>>>,
-    pipeline: Res<BlurRegionsPipeline<N>>,
-    views: Query<(Entity, &ExtractedView), With<BlurRegionsCamera<N>>>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<BlurRegionsPipeline>>,
+    pipeline: Res<BlurRegionsPipeline>,
+    render_device: Res<RenderDevice>,
+    mut texture_cache: ResMut<TextureCache>,
+    views: Query<(Entity, &ExtractedView, &ExtractedCamera, &BlurRegionsCamera)>,
 ) {
-    for (entity, view) in &views {
-        let horizontal_pass = BlurRegionsPass {
-            pass_label: "blur regions (horizontal pass)",
-            bind_group_label: "blur regions bind group (horizontal pass)",
-            pipeline: pipelines.specialize(
-                &pipeline_cache,
-                &pipeline,
-                BlurRegionsPipelineKey {
-                    pass: BlurRegionsPassKey::Horizontal,
-                    hdr: view.hdr,
-                },
-            ),
+    for (entity, view, camera, blur_regions) in &views {
+        let Some(target_size) = camera.physical_target_size else {
+            continue;
         };
 
-        let vertical_pass = BlurRegionsPass {
-            pass_label: "blur regions (vertical pass)",
-            bind_group_label: "blur regions bind group (vertical pass)",
-            pipeline: pipelines.specialize(
+        let mut specialize = |pass| {
+            pipelines.specialize(
                 &pipeline_cache,
                 &pipeline,
-                BlurRegionsPipelineKey {
-                    pass: BlurRegionsPassKey::Vertical,
-                    hdr: view.hdr,
+                BlurRegionsPipelineKey { pass, hdr: view.hdr },
+            )
+        };
+
+        // The compute Gaussian blur falls back to the fragment one where compute isn't available.
+        let algorithm = match blur_regions.algorithm {
+            BlurAlgorithm::GaussianCompute if !pipeline.compute_supported => BlurAlgorithm::Gaussian,
+            algorithm => algorithm,
+        };
+
+        let (passes, prepass) = if algorithm == BlurAlgorithm::Gaussian {
+            let vertical_pass = BlurRegionsPass {
+                pass_label: "blur regions (vertical pass)",
+                bind_group_label: "blur regions bind group (vertical pass)",
+                pipeline: specialize(BlurRegionsPassKey::Vertical),
+            };
+
+            let texture = texture_cache.get(
+                &render_device,
+                TextureDescriptor {
+                    label: Some("blur_regions_horizontal_texture"),
+                    size: Extent3d {
+                        width: target_size.x,
+                        height: target_size.y,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: if view.hdr {
+                        ViewTarget::TEXTURE_FORMAT_HDR
+                    } else {
+                        TextureFormat::bevy_default()
+                    },
+                    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
                 },
-            ),
+            );
+
+            let prepass = BlurPrepass::Horizontal {
+                pipeline: specialize(BlurRegionsPassKey::Horizontal),
+                texture,
+            };
+            (vec![vertical_pass], prepass)
+        } else {
+            let composite_pass = BlurRegionsPass {
+                pass_label: "blur regions (composite pass)",
+                bind_group_label: "blur regions bind group (composite pass)",
+                pipeline: specialize(BlurRegionsPassKey::Composite(algorithm)),
+            };
+
+            let prepass = match algorithm {
+                BlurAlgorithm::GaussianCompute => BlurPrepass::Compute,
+                _ => {
+                    let levels = pyramid_levels(target_size, blur_regions.max_circle_of_confusion());
+                    BlurPrepass::Pyramid {
+                        upsample_pipelines: (1..levels)
+                            .map(|level| specialize(BlurRegionsPassKey::Upsample(algorithm, level)))
+                            .collect(),
+                    }
+                }
+            };
+            (vec![composite_pass], prepass)
+        };
+
+        let scissor = match blur_regions.render_mode {
+            BlurRenderMode::Scissored => blur_regions.scissor_rects(target_size).map(|rects| BlurRegionsScissor {
+                copy_pipeline: specialize(BlurRegionsPassKey::Copy),
+                rects,
+            }),
+            BlurRenderMode::Fullscreen => None,
         };
 
-        commands.entity(entity).insert(BlurRegionsPasses([horizontal_pass, vertical_pass]));
+        commands.entity(entity).insert(BlurRegionsPasses {
+            passes,
+            prepass,
+            scissor,
+        });
     }
 }
 
-impl<const N: usize> SpecializedRenderPipeline for BlurRegionsPipeline<N> {
+impl SpecializedRenderPipeline for BlurRegionsPipeline {
     type Key = BlurRegionsPipelineKey;
 
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = vec![];
+        if !self.storage_buffers_supported {
+            shader_defs.push(ShaderDefVal::UInt(
+                "MAX_BLUR_REGIONS_COUNT".into(),
+                UNIFORM_MAX_BLUR_REGIONS_COUNT as u32,
+            ));
+        }
+        match key.pass {
+            // Only the Dual Kawase and mip chain blurs sample the blur pyramid, which is kept out of the
+            // other pipelines as it isn't supported by every backend.
+            BlurRegionsPassKey::Composite(BlurAlgorithm::DualKawase) => {
+                shader_defs.push("BLUR_PYRAMID".into());
+                shader_defs.push("DUAL_KAWASE".into());
+            }
+            BlurRegionsPassKey::Composite(BlurAlgorithm::MipChain) => shader_defs.push("BLUR_PYRAMID".into()),
+            BlurRegionsPassKey::Upsample(algorithm, level) => {
+                shader_defs.push("BLUR_PYRAMID".into());
+                shader_defs.push(ShaderDefVal::UInt("PYRAMID_LEVEL".into(), level));
+                if algorithm == BlurAlgorithm::DualKawase {
+                    shader_defs.push("DUAL_KAWASE".into());
+                }
+            }
+            BlurRegionsPassKey::Composite(BlurAlgorithm::GaussianCompute) => {
+                shader_defs.push("COMPUTE_GAUSSIAN".into());
+            }
+            _ => {}
+        }
+
         RenderPipelineDescriptor {
             label: Some("blur_regions_pipeline".into()),
             layout: vec![self.layout.clone()],
@@ -252,16 +936,19 @@ impl<const N: usize> SpecializedRenderPipeline for BlurRegionsPipeline<N> {
             push_constant_ranges: vec![],
             fragment: Some(FragmentState {
                 shader: SHADER_HANDLE,
-                shader_defs: vec![ShaderDefVal::UInt("MAX_BLUR_REGIONS_COUNT".into(), N as u32)],
+                shader_defs,
                 entry_point: match key.pass {
                     BlurRegionsPassKey::Horizontal => "horizontal".into(),
                     BlurRegionsPassKey::Vertical => "vertical".into(),
+                    BlurRegionsPassKey::Composite(_) => "composite".into(),
+                    BlurRegionsPassKey::Upsample(..) => "upsample".into(),
+                    BlurRegionsPassKey::Copy => "copy".into(),
                 },
                 targets: vec![Some(ColorTargetState {
-                    format: if key.hdr {
-                        ViewTarget::TEXTURE_FORMAT_HDR
-                    } else {
-                        TextureFormat::bevy_default()
+                    format: match key.pass {
+                        BlurRegionsPassKey::Upsample(..) => PYRAMID_TEXTURE_FORMAT,
+                        _ if key.hdr => ViewTarget::TEXTURE_FORMAT_HDR,
+                        _ => TextureFormat::bevy_default(),
                     },
                     blend: None,
                     write_mask: ColorWrites::ALL,
@@ -270,3 +957,101 @@ impl<const N: usize> SpecializedRenderPipeline for BlurRegionsPipeline<N> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_graph::EmptyNode;
+
+    use super::*;
+
+    fn world_with_graph(graph: impl RenderSubGraph, nodes: &[InternedRenderLabel]) -> World {
+        let mut sub_graph = RenderGraph::default();
+        for node in nodes {
+            sub_graph.add_node(*node, EmptyNode);
+        }
+        let mut render_graph = RenderGraph::default();
+        render_graph.add_sub_graph(graph, sub_graph);
+
+        let mut world = World::new();
+        world.insert_resource(render_graph);
+        world
+    }
+
+    #[test]
+    fn before_bloom_runs_after_the_optional_nodes_that_exist() {
+        let world = world_with_graph(
+            Core3d,
+            &[
+                Node3d::EndMainPass.intern(),
+                Node3d::MotionBlur.intern(),
+                Node3d::Bloom.intern(),
+            ],
+        );
+
+        let edges = BlurRegionsGraphPlacement::BeforeBloom.edges();
+        assert_eq!(edges[0].graph, Core3d.intern());
+        // TAA isn't in the graph, so there is no edge from it.
+        assert_eq!(
+            edges[0].node_edges(&world),
+            [
+                (Node3d::EndMainPass.intern(), BlurRegionsLabel.intern()),
+                (Node3d::MotionBlur.intern(), BlurRegionsLabel.intern()),
+                (BlurRegionsLabel.intern(), Node3d::Bloom.intern()),
+            ]
+        );
+    }
+
+    #[test]
+    fn after_tonemapping_runs_before_anti_aliasing() {
+        let world = world_with_graph(
+            Core2d,
+            &[
+                Node2d::Tonemapping.intern(),
+                Node2d::Fxaa.intern(),
+                Node2d::ContrastAdaptiveSharpening.intern(),
+                Node2d::EndMainPassPostProcessing.intern(),
+            ],
+        );
+
+        let edges = BlurRegionsGraphPlacement::AfterTonemapping.edges();
+        assert_eq!(edges[1].graph, Core2d.intern());
+        assert_eq!(
+            edges[1].node_edges(&world),
+            [
+                (Node2d::Tonemapping.intern(), BlurRegionsLabel.intern()),
+                (BlurRegionsLabel.intern(), Node2d::EndMainPassPostProcessing.intern()),
+                (BlurRegionsLabel.intern(), Node2d::Fxaa.intern()),
+                (BlurRegionsLabel.intern(), Node2d::ContrastAdaptiveSharpening.intern()),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_graphs_and_nodes_are_skipped() {
+        let world = world_with_graph(Core2d, &[Node2d::Bloom.intern()]);
+        let edges = BlurRegionsGraphPlacement::BeforeTonemapping.edges();
+
+        // The 3D graph doesn't exist.
+        assert!(edges[0].node_edges(&world).is_empty());
+        // Tonemapping isn't in the 2D graph, so the node is only placed after bloom.
+        assert_eq!(
+            edges[1].node_edges(&world),
+            [(Node2d::Bloom.intern(), BlurRegionsLabel.intern())]
+        );
+    }
+
+    #[test]
+    fn custom_placements_use_the_given_nodes() {
+        let world = world_with_graph(Core3d, &[Node3d::Prepass.intern(), Node3d::StartMainPass.intern()]);
+        let edges = BlurRegionsGraphPlacement::custom(Core3d, Node3d::Prepass, Node3d::StartMainPass).edges();
+
+        assert_eq!(edges.len(), 1);
+        assert_eq!(
+            edges[0].node_edges(&world),
+            [
+                (Node3d::Prepass.intern(), BlurRegionsLabel.intern()),
+                (BlurRegionsLabel.intern(), Node3d::StartMainPass.intern()),
+            ]
+        );
+    }
+}
@@ -2,14 +2,45 @@
 
 @group(0) @binding(0) var screen_texture: texture_2d<f32>;
 @group(0) @binding(1) var texture_sampler: sampler;
-@group(0) @binding(2) var<uniform> blur_regions: BlurRegionsCamera;
+#ifdef MAX_BLUR_REGIONS_COUNT
+@group(0) @binding(2) var<uniform> blur_regions: BlurRegions;
+#else
+@group(0) @binding(2) var<storage, read> blur_regions: BlurRegions;
+#endif
+@group(0) @binding(3) var mask_texture: texture_2d_array<f32>;
+@group(0) @binding(4) var mask_sampler: sampler;
+@group(0) @binding(5) var silhouette_texture: texture_2d<f32>;
+// The result of the prepass of the blur: the horizontally blurred screen for the Gaussian blur, the
+// downsampled levels of the screen for the Dual Kawase and mip chain blurs, or the blurred screen
+// for the compute Gaussian blur.
+@group(0) @binding(6) var prepass_texture: texture_2d<f32>;
+#ifndef MAX_BLUR_REGIONS_COUNT
+@group(0) @binding(7) var<storage, read> blur_region_tiles: BlurRegionTiles;
+#endif
 
-struct BlurRegionsCamera {
-    circle_of_confusion: f32,
-    padding_0: u32,
-    padding_1: u32,
-    current_regions_count: u32,
+// Platforms without storage buffer support (e.g. WebGL2) fall back to a fixed size uniform array.
+struct BlurRegions {
+    count: u32,
+    // The settings of the camera, used to blur the silhouettes of blur masks and the tilt-shift.
+    defaults: ComputedBlurRegion,
+    tilt_shift: TiltShift,
+#ifdef MAX_BLUR_REGIONS_COUNT
     regions: array<ComputedBlurRegion, #{MAX_BLUR_REGIONS_COUNT}>,
+#else
+    regions: array<ComputedBlurRegion>,
+#endif
+}
+
+// The blur regions binned into tiles of the screen, so that each pixel only tests the regions
+// overlapping its tile. `data` starts with the offset into `data` and the number of regions of each
+// tile, in row-major order, followed by the indices of the regions of each tile.
+struct BlurRegionTiles {
+    tile_size: u32,
+    // The number of columns of tiles, or zero if the regions aren't binned.
+    columns: u32,
+    // The number of rows of tiles, or zero if the regions aren't binned.
+    rows: u32,
+    data: array<u32>,
 }
 
 struct ComputedBlurRegion {
@@ -17,9 +48,75 @@ struct ComputedBlurRegion {
     max_x: f32,
     min_y: f32,
     max_y: f32,
+    rotation: vec2<f32>,
+    gradient_strength: vec2<f32>,
     border_radii: vec4<f32>,
+    shape_params: array<vec4<f32>, 2>,
+    tint: vec4<f32>,
+    mask_uv: vec4<f32>,
+    gradient: vec4<f32>,
+    tint_blend_mode: u32,
+    shape: u32,
+    circle_of_confusion: f32,
+    feather: f32,
+    inverted: u32,
+    mask_layer: i32,
+    saturation: f32,
+    brightness: f32,
+    contrast: f32,
+    grain_intensity: f32,
+    grain_scale: f32,
+    gradient_kind: u32,
+}
+
+// A fullscreen blur that keeps a band across the screen in focus, disabled when the circle of
+// confusion is zero.
+struct TiltShift {
+    // The center of the focus band.
+    center: vec2<f32>,
+    // The direction across the focus band.
+    normal: vec2<f32>,
+    half_height: f32,
+    falloff: f32,
+    circle_of_confusion: f32,
+}
+
+// How much a pixel is blurred.
+struct Blur {
+    // The diameter of the circle of confusion, in physical pixels.
+    circle_of_confusion: f32,
+    // How much of the blurred color is mixed with the unblurred color, from 0 to 1.
+    amount: f32,
+    // The index of the region that covers the position the most, which the tint and color
+    // adjustments are taken from, or `CAMERA_REGION` for the silhouettes of blur masks and the
+    // tilt-shift, which use the settings of the camera.
+    region: u32,
 }
 
+// Must be kept in sync with the shape constants in core.rs
+const SHAPE_ROUNDED_BOX: u32 = 0u;
+const SHAPE_CIRCLE: u32 = 1u;
+const SHAPE_ELLIPSE: u32 = 2u;
+const SHAPE_CAPSULE: u32 = 3u;
+const SHAPE_REGULAR_POLYGON: u32 = 4u;
+const SHAPE_RHOMBUS: u32 = 5u;
+const SHAPE_TRIANGLE: u32 = 6u;
+
+// Must be kept in sync with BlurRegionBlendMode in core.rs
+const BLEND_MODE_ALPHA: u32 = 0u;
+const BLEND_MODE_MULTIPLY: u32 = 1u;
+const BLEND_MODE_SCREEN: u32 = 2u;
+const BLEND_MODE_OVERLAY: u32 = 3u;
+
+// Must be kept in sync with the gradient constants in core.rs
+const GRADIENT_NONE: u32 = 0u;
+const GRADIENT_LINEAR: u32 = 1u;
+const GRADIENT_RADIAL: u32 = 2u;
+
+const CAMERA_REGION: u32 = 0xffffffffu;
+
+const PI: f32 = 3.141592653589793;
+
 // Copied from: bevy_ui/src/render/ui.wgsl
 //
 // The returned value is the shortest distance from the given point to the boundary of the rounded
@@ -53,30 +150,317 @@ fn sd_rounded_box(point: vec2<f32>, size: vec2<f32>, corner_radii: vec4<f32>) ->
     return l + m - radius;
 }
 
-fn is_blurred(position: vec4<f32>) -> bool {
-    for (var i = 0; u32(i) < blur_regions.current_regions_count; i++ ) {
-        let center = vec2(
-            (blur_regions.regions[i].max_x + blur_regions.regions[i].min_x) * 0.5,
-            (blur_regions.regions[i].max_y + blur_regions.regions[i].min_y) * 0.5
-        );
-        let dims = vec2(
-            abs(blur_regions.regions[i].max_x) - abs(blur_regions.regions[i].min_x),
-            abs(blur_regions.regions[i].max_y) - abs(blur_regions.regions[i].min_y)
+// The signed distance functions below are adapted from:
+// https://iquilezles.org/articles/distfunctions2d/
+//
+// As with `sd_rounded_box`, `point` is relative to the center of the shape, negative values are
+// inside the shape and positive values are outside.
+
+fn sd_circle(point: vec2<f32>, radius: f32) -> f32 {
+    return length(point) - radius;
+}
+
+// An approximation, exact on the boundary of the ellipse.
+fn sd_ellipse(point: vec2<f32>, half_size: vec2<f32>) -> f32 {
+    let k0 = length(point / half_size);
+    let k1 = length(point / (half_size * half_size));
+    // Avoid dividing by zero at the center of the ellipse.
+    return select(k0 * (k0 - 1.0) / k1, -min(half_size.x, half_size.y), k1 == 0.0);
+}
+
+// A capsule aligned with the y axis.
+fn sd_capsule(point: vec2<f32>, radius: f32, half_length: f32) -> f32 {
+    let p = vec2(point.x, point.y - clamp(point.y, -half_length, half_length));
+    return length(p) - radius;
+}
+
+// A regular polygon with a vertex at the top of the screen.
+fn sd_regular_polygon(point: vec2<f32>, circumradius: f32, sides: f32) -> f32 {
+    let half_angle = PI / sides;
+    let apothem = circumradius * cos(half_angle);
+    let half_edge = circumradius * sin(half_angle);
+    // The angle of the point relative to the middle of the first edge clockwise of the top vertex,
+    // snapped to the middle of the nearest edge.
+    let angle = atan2(point.y, point.x) - (half_angle - 0.5 * PI);
+    let edge_angle = round(angle / (2.0 * half_angle)) * (2.0 * half_angle) + (half_angle - 0.5 * PI);
+    // Rotate the point so that the nearest edge is vertical, at x = apothem.
+    let c = cos(edge_angle);
+    let s = sin(edge_angle);
+    let p = vec2(c * point.x + s * point.y, c * point.y - s * point.x);
+    return length(p - vec2(apothem, clamp(p.y, -half_edge, half_edge))) * sign(p.x - apothem);
+}
+
+fn sd_rhombus(point: vec2<f32>, half_diagonals: vec2<f32>) -> f32 {
+    let p = abs(point);
+    let b = half_diagonals;
+    let ndot = (b.x - 2.0 * p.x) * b.x - (b.y - 2.0 * p.y) * b.y;
+    let h = clamp(ndot / dot(b, b), -1.0, 1.0);
+    let d = length(p - 0.5 * b * vec2(1.0 - h, 1.0 + h));
+    return d * sign(p.x * b.y + p.y * b.x - b.x * b.y);
+}
+
+fn sd_triangle(point: vec2<f32>, a: vec2<f32>, b: vec2<f32>, c: vec2<f32>) -> f32 {
+    let e0 = b - a;
+    let e1 = c - b;
+    let e2 = a - c;
+    let v0 = point - a;
+    let v1 = point - b;
+    let v2 = point - c;
+    let pq0 = v0 - e0 * clamp(dot(v0, e0) / dot(e0, e0), 0.0, 1.0);
+    let pq1 = v1 - e1 * clamp(dot(v1, e1) / dot(e1, e1), 0.0, 1.0);
+    let pq2 = v2 - e2 * clamp(dot(v2, e2) / dot(e2, e2), 0.0, 1.0);
+    // The winding order of the vertices isn't known, so the sign of the cross products are
+    // normalized with it.
+    let s = sign(e0.x * e2.y - e0.y * e2.x);
+    let d = min(
+        min(
+            vec2(dot(pq0, pq0), s * (v0.x * e0.y - v0.y * e0.x)),
+            vec2(dot(pq1, pq1), s * (v1.x * e1.y - v1.y * e1.x))
+        ),
+        vec2(dot(pq2, pq2), s * (v2.x * e2.y - v2.y * e2.x))
+    );
+    return -sqrt(d.x) * sign(d.y);
+}
+
+// Returns the given point relative to the center of the region, with the rotation of the region
+// undone so that its shape can be evaluated in its local frame.
+fn local_position(point: vec2<f32>, region: ComputedBlurRegion) -> vec2<f32> {
+    let center = vec2(
+        (region.max_x + region.min_x) * 0.5,
+        (region.max_y + region.min_y) * 0.5
+    );
+    let offset = point - center;
+    let c = region.rotation.x;
+    let s = region.rotation.y;
+    return vec2(c * offset.x + s * offset.y, c * offset.y - s * offset.x);
+}
+
+// Returns the signed distance from the given point to the boundary of the region.
+fn sd_blur_region(point: vec2<f32>, region: ComputedBlurRegion) -> f32 {
+    let p = local_position(point, region);
+    let params = region.shape_params[0];
+
+    switch region.shape {
+        case SHAPE_CIRCLE: {
+            return sd_circle(p, params.x);
+        }
+        case SHAPE_ELLIPSE: {
+            return sd_ellipse(p, params.xy);
+        }
+        case SHAPE_CAPSULE: {
+            return sd_capsule(p, params.x, params.y);
+        }
+        case SHAPE_REGULAR_POLYGON: {
+            return sd_regular_polygon(p, params.x, params.y);
+        }
+        case SHAPE_RHOMBUS: {
+            return sd_rhombus(p, params.xy);
+        }
+        case SHAPE_TRIANGLE: {
+            return sd_triangle(p, params.xy, params.zw, region.shape_params[1].xy);
+        }
+        default: {
+            let dims = params.xy * 2.0;
+            let half_smallest_dimension = min(dims.x, dims.y) * 0.5;
+            return sd_rounded_box(p, dims, min(region.border_radii, vec4(half_smallest_dimension)));
+        }
+    }
+}
+
+// Returns the opacity of the mask of the region at the given point, or 1.0 if the region has no
+// mask. Masks are stretched over the rect of the region.
+fn mask_opacity(point: vec2<f32>, region: ComputedBlurRegion) -> f32 {
+    if region.mask_layer < 0 {
+        return 1.0;
+    }
+    let uv = local_position(point, region) / (2.0 * region.shape_params[0].xy) + 0.5;
+    if any(uv < vec2(0.0)) || any(uv > vec2(1.0)) {
+        return 0.0;
+    }
+    let mask_uv = mix(region.mask_uv.xy, region.mask_uv.zw, uv);
+    return textureSampleLevel(mask_texture, mask_sampler, mask_uv, region.mask_layer, 0.0).r;
+}
+
+// Returns the circle of confusion of the region at the given point, scaled by the gradient of the
+// region.
+fn region_circle_of_confusion(point: vec2<f32>, region: ComputedBlurRegion) -> f32 {
+    var t: f32;
+    switch region.gradient_kind {
+        case GRADIENT_LINEAR: {
+            t = dot(local_position(point, region), region.gradient.xy) + region.gradient.z;
+        }
+        case GRADIENT_RADIAL: {
+            t = length(local_position(point, region) - region.gradient.xy) * region.gradient.z;
+        }
+        default: {
+            return region.circle_of_confusion;
+        }
+    }
+    let strength = mix(region.gradient_strength.x, region.gradient_strength.y, clamp(t, 0.0, 1.0));
+    return region.circle_of_confusion * max(strength, 0.0);
+}
+
+// Returns how much of the blur region applies at the given signed distance from its boundary,
+// fading out over the feather band along the inside edge.
+fn blur_region_amount(distance: f32, feather: f32) -> f32 {
+    if feather <= 0.0 {
+        return select(0.0, 1.0, distance <= 0.0);
+    }
+    return clamp(-distance / feather, 0.0, 1.0);
+}
+
+// Returns how the given position is blurred by the tilt-shift, which ramps up from nothing at the
+// edge of the focus band.
+fn tilt_shift_at(position: vec2<f32>) -> Blur {
+    let tilt_shift = blur_regions.tilt_shift;
+    if tilt_shift.circle_of_confusion <= 0.0 {
+        return Blur(0.0, 0.0, CAMERA_REGION);
+    }
+    let distance = abs(dot(position - tilt_shift.center, tilt_shift.normal)) - tilt_shift.half_height;
+    if distance <= 0.0 {
+        return Blur(0.0, 0.0, CAMERA_REGION);
+    }
+    let strength = select(1.0, min(distance / tilt_shift.falloff, 1.0), tilt_shift.falloff > 0.0);
+    return Blur(tilt_shift.circle_of_confusion * strength, 1.0, CAMERA_REGION);
+}
+
+// Returns how the given position is blurred by the regions covering it. When regions overlap, the
+// strongest blur wins.
+//
+// Inverted regions blur everything outside of them, so a position is only blurred by inverted
+// regions when it is outside of all of them.
+//
+// The silhouettes of blur masks are blurred in the same way as regions, while regions are drawn
+// over the tilt-shift.
+fn blur_at(position: vec4<f32>) -> Blur {
+    var blur = tilt_shift_at(position.xy);
+    var inverted_blur = Blur(0.0, 1.0, 0u);
+    var has_inverted = false;
+
+    // Only the regions overlapping the tile of the position are tested, when the regions are binned.
+    var count = blur_regions.count;
+    var tile_start = 0u;
+#ifndef MAX_BLUR_REGIONS_COUNT
+    let tiled = blur_region_tiles.columns > 0u;
+    if tiled {
+        // Positions scaled up from a level of the blur pyramid may be past the last partial tile.
+        let tile = min(
+            vec2<u32>(max(position.xy, vec2(0.0))) / blur_region_tiles.tile_size,
+            vec2(blur_region_tiles.columns, blur_region_tiles.rows) - 1u
         );
-        let half_smallest_dimension = min(dims.x, dims.y) * 0.5;
+        let index = (tile.y * blur_region_tiles.columns + tile.x) * 2u;
+        tile_start = blur_region_tiles.data[index];
+        count = blur_region_tiles.data[index + 1u];
+    }
+#endif
+
+    for (var j = 0u; j < count; j++ ) {
+        var i = j;
+#ifndef MAX_BLUR_REGIONS_COUNT
+        if tiled {
+            i = blur_region_tiles.data[tile_start + j];
+        }
+#endif
+        let region = blur_regions.regions[i];
+        let distance = sd_blur_region(position.xy, region);
+        let mask = mask_opacity(position.xy, region);
+        if region.inverted != 0u {
+            has_inverted = true;
+            inverted_blur.circle_of_confusion = max(
+                inverted_blur.circle_of_confusion,
+                region_circle_of_confusion(position.xy, region)
+            );
+            let amount = max(blur_region_amount(-distance, region.feather), 1.0 - mask);
+            inverted_blur.amount = min(inverted_blur.amount, amount);
+            inverted_blur.region = i;
+            continue;
+        }
 
-        if sd_rounded_box(
-            position.xy - center,
-            dims,
-            min(blur_regions.regions[i].border_radii, vec4(half_smallest_dimension))) <= 0.0 {
-          return true;
+        let amount = blur_region_amount(distance, region.feather) * mask;
+        if amount > 0.0 {
+            blur.circle_of_confusion = max(blur.circle_of_confusion, region_circle_of_confusion(position.xy, region));
+            if amount >= blur.amount {
+                blur.region = i;
+            }
+            blur.amount = max(blur.amount, amount);
         }
     }
-    return false;
+
+    if has_inverted && inverted_blur.amount > 0.0 {
+        blur.circle_of_confusion = max(blur.circle_of_confusion, inverted_blur.circle_of_confusion);
+        if inverted_blur.amount >= blur.amount {
+            blur.region = inverted_blur.region;
+        }
+        blur.amount = max(blur.amount, inverted_blur.amount);
+    }
+
+    let texel = min(vec2<i32>(position.xy), vec2<i32>(textureDimensions(silhouette_texture)) - 1);
+    let silhouette = textureLoad(silhouette_texture, texel, 0).r;
+    if silhouette > 0.0 {
+        blur.circle_of_confusion = max(blur.circle_of_confusion, blur_regions.defaults.circle_of_confusion);
+        if silhouette >= blur.amount {
+            blur.region = CAMERA_REGION;
+        }
+        blur.amount = max(blur.amount, silhouette);
+    }
+    return blur;
+}
+
+// Adjusts the saturation, brightness and contrast of the given linear color.
+fn adjust_color(color: vec3<f32>, saturation: f32, brightness: f32, contrast: f32) -> vec3<f32> {
+    // Contrast is scaled around middle grey, which is 0.18 in linear color space.
+    var adjusted = (color * brightness - 0.18) * contrast + 0.18;
+    let luminance = dot(adjusted, vec3(0.2126, 0.7152, 0.0722));
+    adjusted = mix(vec3(luminance), adjusted, saturation);
+    return max(adjusted, vec3(0.0));
+}
+
+// Returns a pseudo-random value between 0 and 1 for the given cell.
+fn hash(cell: vec2<u32>) -> f32 {
+    var n = cell.x * 1973u + cell.y * 9277u;
+    n = (n << 13u) ^ n;
+    n = n * (n * n * 15731u + 789221u) + 1376312589u;
+    return f32(n & 0x7fffffffu) / f32(0x7fffffff);
+}
+
+// Scatters grain over the given linear color. The grain is multiplicative so that it is equally
+// visible in dark and light areas.
+fn apply_grain(color: vec3<f32>, position: vec2<f32>, intensity: f32, scale: f32) -> vec3<f32> {
+    if intensity <= 0.0 {
+        return color;
+    }
+    let cell = vec2<u32>(max(position / max(scale, 1.0), vec2(0.0)));
+    return color * (1.0 + (hash(cell) - 0.5) * 2.0 * intensity);
+}
+
+// Blends the tint over the given color, using the alpha of the tint as the strength of the blend.
+fn apply_tint(color: vec3<f32>, tint: vec4<f32>, blend_mode: u32) -> vec3<f32> {
+    var blended: vec3<f32>;
+    switch blend_mode {
+        case BLEND_MODE_MULTIPLY: {
+            blended = color * tint.rgb;
+        }
+        case BLEND_MODE_SCREEN: {
+            blended = 1.0 - (1.0 - color) * (1.0 - tint.rgb);
+        }
+        case BLEND_MODE_OVERLAY: {
+            blended = select(
+                1.0 - 2.0 * (1.0 - color) * (1.0 - tint.rgb),
+                2.0 * color * tint.rgb,
+                color < vec3(0.5)
+            );
+        }
+        default: {
+            blended = tint.rgb;
+        }
+    }
+    return mix(color, blended, tint.a);
 }
 
 // Performs a single direction of the separable Gaussian blur kernel.
 //
+// * `source` is the texture being blurred.
+//
 // * `frag_coord` is the screen-space pixel coordinate of the fragment (i.e. the
 //   `position` input to the fragment).
 //
@@ -91,7 +475,7 @@ fn is_blurred(position: vec4<f32>) -> bool {
 //
 // ATTRIBUTION: This code and comments for this function was originally
 // contributed to bevy under the MIT or Apache 2 licenses.
-fn gaussian_blur(frag_coord: vec4<f32>, coc: f32, frag_offset: vec2<f32>) -> vec4<f32> {
+fn gaussian_blur(source: texture_2d<f32>, frag_coord: vec4<f32>, coc: f32, frag_offset: vec2<f32>) -> vec4<f32> {
     // Usually σ (the standard deviation) is half the radius, and the radius is
     // half the CoC. So we multiply by 0.25.
     let sigma = coc * 0.25;
@@ -99,8 +483,8 @@ fn gaussian_blur(frag_coord: vec4<f32>, coc: f32, frag_offset: vec2<f32>) -> vec
     // 1.5σ is a good, somewhat aggressive default for support—the number of
     // texels on each side of the center that we process.
     let support = i32(ceil(sigma * 1.5));
-    let uv = frag_coord.xy / vec2<f32>(textureDimensions(screen_texture));
-    let offset = frag_offset / vec2<f32>(textureDimensions(screen_texture));
+    let uv = frag_coord.xy / vec2<f32>(textureDimensions(source));
+    let offset = frag_offset / vec2<f32>(textureDimensions(source));
 
     // The probability density function of the Gaussian blur is (up to constant factors) `exp(-1 / 2σ² *
     // x²). We precalculate the constant factor here to avoid having to
@@ -109,7 +493,7 @@ fn gaussian_blur(frag_coord: vec4<f32>, coc: f32, frag_offset: vec2<f32>) -> vec
 
     // Accumulate samples on both sides of the current texel. Go two at a time,
     // taking advantage of bilinear filtering.
-    var sum = textureSampleLevel(screen_texture, texture_sampler, uv, 0.0).rgb;
+    var sum = textureSampleLevel(source, texture_sampler, uv, 0.0).rgb;
     var weight_sum = 1.0;
     for (var i = 1; i <= support; i += 2) {
         // This is a well-known trick to reduce the number of needed texture
@@ -132,8 +516,8 @@ fn gaussian_blur(frag_coord: vec4<f32>, coc: f32, frag_offset: vec2<f32>) -> vec
         let weight = w0 + w1;
 
         sum += (
-            textureSampleLevel(screen_texture, texture_sampler, uv + uv_offset, 0.0).rgb +
-            textureSampleLevel(screen_texture, texture_sampler, uv - uv_offset, 0.0).rgb
+            textureSampleLevel(source, texture_sampler, uv + uv_offset, 0.0).rgb +
+            textureSampleLevel(source, texture_sampler, uv - uv_offset, 0.0).rgb
         ) * weight;
         weight_sum += weight * 2.0;
     }
@@ -141,20 +525,271 @@ fn gaussian_blur(frag_coord: vec4<f32>, coc: f32, frag_offset: vec2<f32>) -> vec
     return vec4(sum / weight_sum, 1.0);
 }
 
+#ifdef BLUR_PYRAMID
+// The level of the blur pyramid matching the given circle of confusion, where level 0 is the
+// screen and each level after it roughly doubles the circle of confusion of the level before it.
+fn pyramid_level(coc: f32) -> f32 {
+    return log2(max(coc * 0.5, 1.0));
+}
+
+// Upsamples the blurred level of the blur pyramid below the one being rendered, with the
+// upsampling filter of the blur so that its texels don't look blocky.
+fn upsample_pyramid(uv: vec2<f32>) -> vec3<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(prepass_texture));
+#ifdef DUAL_KAWASE
+    // The upsampling filter of the Dual Kawase blur, from: Bandwidth-Efficient Rendering,
+    // Marius Bjørge, SIGGRAPH 2015
+    let o = texel * 0.5;
+    var sum = textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(-2.0 * o.x, 0.0), 0.0).rgb;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(2.0 * o.x, 0.0), 0.0).rgb;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(0.0, -2.0 * o.y), 0.0).rgb;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(0.0, 2.0 * o.y), 0.0).rgb;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(-o.x, -o.y), 0.0).rgb * 2.0;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(o.x, -o.y), 0.0).rgb * 2.0;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(-o.x, o.y), 0.0).rgb * 2.0;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(o.x, o.y), 0.0).rgb * 2.0;
+    return sum / 12.0;
+#else
+    // A 3x3 tent filter, from: Next Generation Post Processing in Call of Duty: Advanced Warfare,
+    // Jorge Jimenez, SIGGRAPH 2014
+    var sum = textureSampleLevel(prepass_texture, mask_sampler, uv, 0.0).rgb * 4.0;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(-texel.x, 0.0), 0.0).rgb * 2.0;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(texel.x, 0.0), 0.0).rgb * 2.0;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(0.0, -texel.y), 0.0).rgb * 2.0;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(0.0, texel.y), 0.0).rgb * 2.0;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(-texel.x, -texel.y), 0.0).rgb;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(texel.x, -texel.y), 0.0).rgb;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(-texel.x, texel.y), 0.0).rgb;
+    sum += textureSampleLevel(prepass_texture, mask_sampler, uv + vec2(texel.x, texel.y), 0.0).rgb;
+    return sum / 16.0;
+#endif
+}
+
+// Blurs the fragment by upsampling the largest level of the blur pyramid, which is already blurred
+// by the circle of confusion of each pixel, and mixing it with the screen for circles of confusion
+// smaller than the first level.
+fn pyramid_blur(frag_coord: vec4<f32>, coc: f32) -> vec4<f32> {
+    let uv = frag_coord.xy / vec2<f32>(textureDimensions(screen_texture));
+    let color = textureSampleLevel(screen_texture, texture_sampler, uv, 0.0).rgb;
+    let blurred = mix(color, upsample_pyramid(uv), clamp(pyramid_level(coc), 0.0, 1.0));
+    return vec4(blurred, 1.0);
+}
+#endif
+
+// Applies the color adjustments, tint and grain of the region to the blurred color, and mixes it
+// with the unblurred color.
+//
+// These are only applied in the final pass so that they are only applied once, and so that they
+// aren't blurred.
+fn finish_blur(color: vec4<f32>, blurred: vec4<f32>, blur: Blur, position: vec2<f32>) -> vec4<f32> {
+    var region = blur_regions.defaults;
+    if blur.region != CAMERA_REGION {
+        region = blur_regions.regions[blur.region];
+    }
+    let adjusted = adjust_color(blurred.rgb, region.saturation, region.brightness, region.contrast);
+    let tinted = apply_tint(adjusted, region.tint, region.tint_blend_mode);
+    let grained = apply_grain(tinted, position, region.grain_intensity, region.grain_scale);
+    return mix(color, vec4(grained, blurred.a), blur.amount);
+}
+
 @fragment
 fn horizontal(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
-    if !is_blurred(in.position) {
-        return textureSample(screen_texture, texture_sampler, in.uv);
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let blur = blur_at(in.position);
+    if blur.amount <= 0.0 || blur.circle_of_confusion <= 0.0 {
+        return color;
     }
 
-    return gaussian_blur(in.position, blur_regions.circle_of_confusion, vec2(1.0, 0.0));
+    // The blur is only mixed with the screen by the amount of the region in the vertical pass.
+    return gaussian_blur(screen_texture, in.position, blur.circle_of_confusion, vec2(1.0, 0.0));
 }
 
 @fragment
 fn vertical(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
-    if !is_blurred(in.position) {
-        return textureSample(screen_texture, texture_sampler, in.uv);
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let blur = blur_at(in.position);
+    if blur.amount <= 0.0 {
+        return color;
     }
 
-    return gaussian_blur(in.position, blur_regions.circle_of_confusion, vec2(0.0, 1.0));
+    var blurred = color;
+    if blur.circle_of_confusion > 0.0 {
+        blurred = gaussian_blur(prepass_texture, in.position, blur.circle_of_confusion, vec2(0.0, 1.0));
+    }
+
+    return finish_blur(color, blurred, blur, in.position.xy);
+}
+
+#ifdef PYRAMID_LEVEL
+// Upsamples the blurred level below level `PYRAMID_LEVEL` of the blur pyramid, bound as the
+// prepass texture, into this level, bound as the screen texture.
+//
+// Where the circle of confusion of the pixel is at or below this level, the level is kept as it
+// was downsampled, and where it is a level or more above it, the blurrier level below replaces it,
+// so that once every level is upsampled, each pixel is blurred by the level matching its circle of
+// confusion.
+@fragment
+fn upsample(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let position = in.position.xy * f32(1u << #{PYRAMID_LEVEL}u);
+    let blur = blur_at(vec4(position, 0.0, 1.0));
+    var coc = 0.0;
+    if blur.amount > 0.0 {
+        coc = blur.circle_of_confusion;
+    }
+
+    let downsampled = textureSampleLevel(screen_texture, texture_sampler, in.uv, 0.0).rgb;
+    let t = clamp(pyramid_level(coc) - f32(#{PYRAMID_LEVEL}u), 0.0, 1.0);
+    return vec4(mix(downsampled, upsample_pyramid(in.uv), t), 1.0);
+}
+#endif
+
+// Copies the screen, so that only the bounds of the blur regions need to be blurred when the
+// passes are scissored.
+@fragment
+fn copy(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(screen_texture, texture_sampler, in.uv);
+}
+
+// Blurs with the blur pyramid in a single pass, for the Dual Kawase and mip chain blurs.
+@fragment
+fn composite(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let blur = blur_at(in.position);
+    if blur.amount <= 0.0 {
+        return color;
+    }
+
+    var blurred = color;
+    if blur.circle_of_confusion > 0.0 {
+#ifdef COMPUTE_GAUSSIAN
+        blurred = vec4(textureLoad(prepass_texture, vec2<i32>(in.position.xy), 0).rgb, 1.0);
+#endif
+#ifdef BLUR_PYRAMID
+        blurred = pyramid_blur(in.position, blur.circle_of_confusion);
+#endif
+    }
+
+    return finish_blur(color, blurred, blur, in.position.xy);
+}
+
+#ifdef COMPUTE_GAUSSIAN
+@group(1) @binding(0) var compute_input: texture_2d<f32>;
+@group(1) @binding(1) var compute_output: texture_storage_2d<rgba16float, write>;
+
+// Must be kept in sync with COMPUTE_WORKGROUP_SIZE in compute.rs
+const COMPUTE_WORKGROUP_SIZE: u32 = 128u;
+// The max number of pixels on each side of a pixel that the compute passes sample, enough for a
+// circle of confusion of 340 pixels.
+const COMPUTE_MAX_SUPPORT: u32 = 128u;
+
+// The pixels of the run blurred by the workgroup, with `COMPUTE_MAX_SUPPORT` pixels on each side.
+var<workgroup> compute_pixels: array<vec4<f32>, 384>;
+// The Gaussian weights shared by the workgroup, indexed by the distance from the center pixel.
+var<workgroup> compute_weights: array<f32, 129>;
+// The bits of the smallest and largest circles of confusion of the workgroup, which order the same
+// way as the floats since they are never negative.
+var<workgroup> compute_min_coc: atomic<u32>;
+var<workgroup> compute_max_coc: atomic<u32>;
+// The circle of confusion of every pixel of the workgroup, or -1 if they differ.
+var<workgroup> compute_shared_coc: f32;
+
+// Returns the pixel at the given distance along the run blurred by the workgroup, on the given line.
+fn compute_pixel(along: i32, line: i32, horizontal: bool) -> vec2<i32> {
+    return select(vec2(line, along), vec2(along, line), horizontal);
+}
+
+// Performs a single direction of the separable Gaussian blur for a run of `COMPUTE_WORKGROUP_SIZE`
+// pixels along a row or column. The pixels of the run are loaded into workgroup memory once,
+// rather than being sampled by every pixel that they contribute to.
+//
+// When every pixel of the run has the same circle of confusion, which is the common case, the
+// weights are computed once for the whole workgroup rather than with `exp()` for every sample.
+fn compute_gaussian_blur(workgroup_id: vec3<u32>, local_index: u32, horizontal: bool) {
+    let size = vec2<i32>(textureDimensions(compute_input));
+    let start = i32(workgroup_id.x * COMPUTE_WORKGROUP_SIZE);
+    let line = i32(workgroup_id.y);
+    let position = compute_pixel(start + i32(local_index), line, horizontal);
+    let inside = all(position < size);
+
+    for (var i = local_index; i < COMPUTE_WORKGROUP_SIZE + 2u * COMPUTE_MAX_SUPPORT; i += COMPUTE_WORKGROUP_SIZE) {
+        let pixel = compute_pixel(start + i32(i) - i32(COMPUTE_MAX_SUPPORT), line, horizontal);
+        compute_pixels[i] = textureLoad(compute_input, clamp(pixel, vec2(0), size - 1), 0);
+    }
+    if local_index == 0u {
+        atomicStore(&compute_min_coc, 0xffffffffu);
+        atomicStore(&compute_max_coc, 0u);
+    }
+    workgroupBarrier();
+
+    var coc = 0.0;
+    if inside {
+        let blur = blur_at(vec4(vec2<f32>(position) + 0.5, 0.0, 1.0));
+        if blur.amount > 0.0 {
+            coc = blur.circle_of_confusion;
+        }
+    }
+    atomicMin(&compute_min_coc, bitcast<u32>(coc));
+    atomicMax(&compute_max_coc, bitcast<u32>(coc));
+    workgroupBarrier();
+
+    if local_index == 0u {
+        let min_coc = atomicLoad(&compute_min_coc);
+        let max_coc = atomicLoad(&compute_max_coc);
+        compute_shared_coc = select(-1.0, bitcast<f32>(max_coc), min_coc == max_coc);
+    }
+    let shared_coc = workgroupUniformLoad(&compute_shared_coc);
+    if shared_coc > 0.0 {
+        let sigma = shared_coc * 0.25;
+        let support = min(u32(ceil(sigma * 1.5)), COMPUTE_MAX_SUPPORT);
+        let exp_factor = -1.0 / (2.0 * sigma * sigma);
+        for (var i = local_index; i <= support; i += COMPUTE_WORKGROUP_SIZE) {
+            compute_weights[i] = exp(exp_factor * f32(i * i));
+        }
+    }
+    workgroupBarrier();
+
+    if !inside {
+        return;
+    }
+
+    let center = local_index + COMPUTE_MAX_SUPPORT;
+    if coc <= 0.0 {
+        textureStore(compute_output, position, compute_pixels[center]);
+        return;
+    }
+
+    // As in `gaussian_blur`, σ is a quarter of the circle of confusion and the support is 1.5σ.
+    let sigma = coc * 0.25;
+    let support = min(u32(ceil(sigma * 1.5)), COMPUTE_MAX_SUPPORT);
+    let exp_factor = -1.0 / (2.0 * sigma * sigma);
+    var sum = compute_pixels[center].rgb;
+    var weight_sum = 1.0;
+    for (var i = 1u; i <= support; i++) {
+        var weight: f32;
+        if shared_coc > 0.0 {
+            weight = compute_weights[i];
+        } else {
+            weight = exp(exp_factor * f32(i * i));
+        }
+        sum += (compute_pixels[center - i].rgb + compute_pixels[center + i].rgb) * weight;
+        weight_sum += weight * 2.0;
+    }
+    textureStore(compute_output, position, vec4(sum / weight_sum, 1.0));
+}
+
+@compute @workgroup_size(128, 1, 1)
+fn horizontal_compute(
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    compute_gaussian_blur(workgroup_id, local_index, true);
+}
+
+@compute @workgroup_size(128, 1, 1)
+fn vertical_compute(
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    compute_gaussian_blur(workgroup_id, local_index, false);
 }
+#endif
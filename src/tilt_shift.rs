@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::ShaderType;
+
+/// A fullscreen blur that keeps a band across the screen in focus and blurs everything else,
+/// increasingly so further away from the band, making the scene look like a miniature.
+///
+/// Set [`BlurRegionsCamera::tilt_shift`](crate::BlurRegionsCamera::tilt_shift) to enable it.
+/// Blur regions are drawn over the tilt-shift blur.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TiltShift {
+    /// The position of the center of the focus band, from `0.0` at the top of the viewport to
+    /// `1.0` at the bottom.
+    pub position: f32,
+    /// The height of the focus band, as a fraction of the height of the viewport. Everything
+    /// within the band is left unblurred.
+    pub band_height: f32,
+    /// The distance from the edge of the focus band over which the blur ramps up to
+    /// `circle_of_confusion`, as a fraction of the height of the viewport.
+    pub falloff: f32,
+    /// Rotates the focus band counterclockwise around its center.
+    pub rotation: Rot2,
+    /// The diameter of the circle of confusion furthest away from the focus band.
+    /// In physical pixels.
+    pub circle_of_confusion: f32,
+}
+
+impl Default for TiltShift {
+    fn default() -> Self {
+        TiltShift {
+            position: 0.5,
+            band_height: 0.2,
+            falloff: 0.3,
+            rotation: Rot2::IDENTITY,
+            circle_of_confusion: 40.0,
+        }
+    }
+}
+
+impl TiltShift {
+    /// Lays out the tilt-shift for the shader, in the physical pixels of the given viewport.
+    pub(crate) fn to_gpu(self, viewport: UVec4) -> GpuTiltShift {
+        let offset = viewport.xy().as_vec2();
+        let size = viewport.zw().as_vec2();
+        // Rotations use a y up coordinate system, while the shader is in screen space with y down.
+        let normal = self.rotation.inverse() * Vec2::Y;
+        GpuTiltShift {
+            center: offset + Vec2::new(0.5, self.position) * size,
+            normal,
+            half_height: self.band_height * size.y * 0.5,
+            falloff: self.falloff * size.y,
+            circle_of_confusion: self.circle_of_confusion,
+        }
+    }
+}
+
+/// The tilt-shift of a camera, in physical pixels. A `circle_of_confusion` of zero disables it.
+#[derive(Default, Debug, Clone, ShaderType)]
+pub(crate) struct GpuTiltShift {
+    center: Vec2,
+    normal: Vec2,
+    half_height: f32,
+    falloff: f32,
+    circle_of_confusion: f32,
+}
@@ -0,0 +1,348 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::BlurRegionSettings;
+use crate::BlurRegionsCamera;
+use crate::BlurRegionsSystems;
+
+pub struct BlurTransitionPlugin;
+
+impl Plugin for BlurTransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BlurTransitionFinished>().add_systems(
+            Last,
+            (
+                tick_blur_transitions.before(BlurRegionsSystems::Compute),
+                blur_fullscreen_transitions.in_set(BlurRegionsSystems::Compute),
+            ),
+        );
+    }
+}
+
+/// Animates the blur of a blur region over time.
+///
+/// Add this component to any of the following to animate its blur:
+/// - a Bevy UI Node with a [`BlurRegion`](crate::BlurRegion)
+/// - an entity with a [`WorldBlurRegion`](crate::WorldBlurRegion)
+/// - an entity with a [`ScreenBlurRegion`](crate::ScreenBlurRegion)
+/// - an entity with a [`BoundsBlurRegion`](crate::BoundsBlurRegion)
+/// - a camera with a [`BlurRegionsCamera`], to animate a blur covering the whole screen
+///
+/// Once the transition has finished, the blur stays at the `to` state and a
+/// [`BlurTransitionFinished`] event is sent.
+#[derive(Component, Debug, Clone)]
+pub struct BlurTransition {
+    /// How long the transition takes.
+    pub duration: Duration,
+    /// How the transition progresses over its duration.
+    pub easing: BlurTransitionEasing,
+    /// The clock that the transition advances with.
+    pub clock: BlurTransitionClock,
+    /// The state of the blur at the start of the transition.
+    pub from: BlurTransitionState,
+    /// The state of the blur at the end of the transition.
+    pub to: BlurTransitionState,
+    elapsed: Duration,
+    finished: bool,
+}
+
+impl BlurTransition {
+    pub fn new(duration: Duration, from: BlurTransitionState, to: BlurTransitionState) -> Self {
+        BlurTransition {
+            duration,
+            easing: BlurTransitionEasing::default(),
+            clock: BlurTransitionClock::default(),
+            from,
+            to,
+            elapsed: Duration::ZERO,
+            finished: false,
+        }
+    }
+
+    pub fn with_easing(mut self, easing: BlurTransitionEasing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: BlurTransitionClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Plays the transition backwards from its current state, such as when closing a menu whose
+    /// opening transition hasn't finished yet.
+    pub fn reverse(&mut self) {
+        std::mem::swap(&mut self.from, &mut self.to);
+        self.elapsed = self.duration.saturating_sub(self.elapsed);
+        self.finished = false;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The current state of the blur.
+    pub fn state(&self) -> BlurTransitionState {
+        let t = if self.duration.is_zero() {
+            1.0
+        } else {
+            (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).clamp(0.0, 1.0)
+        };
+        self.from.lerp(&self.to, self.easing.ease(t))
+    }
+
+    /// Overrides the given settings with the current state of the blur.
+    pub fn apply(&self, settings: BlurRegionSettings) -> BlurRegionSettings {
+        let state = self.state();
+        BlurRegionSettings {
+            circle_of_confusion: state.circle_of_confusion,
+            tint: state.tint,
+            ..settings
+        }
+    }
+}
+
+/// The values of a blur that are animated by a [`BlurTransition`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlurTransitionState {
+    /// See [`BlurRegionSettings::circle_of_confusion`].
+    pub circle_of_confusion: f32,
+    /// See [`BlurRegionSettings::tint`].
+    pub tint: Color,
+}
+
+impl Default for BlurTransitionState {
+    fn default() -> Self {
+        BlurTransitionState {
+            circle_of_confusion: 0.0,
+            tint: Color::NONE,
+        }
+    }
+}
+
+impl BlurTransitionState {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        BlurTransitionState {
+            circle_of_confusion: self.circle_of_confusion.lerp(other.circle_of_confusion, t),
+            // Tints are blended in linear color space by the shader, so they are interpolated in it too.
+            tint: self.tint.to_linear().mix(&other.tint.to_linear(), t).into(),
+        }
+    }
+}
+
+/// How a [`BlurTransition`] progresses over its duration.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum BlurTransitionEasing {
+    Linear,
+    QuadraticIn,
+    QuadraticOut,
+    QuadraticInOut,
+    CubicIn,
+    #[default]
+    CubicOut,
+    CubicInOut,
+    /// Maps the linear progress of the transition, from 0 to 1, to the eased progress.
+    Custom(fn(f32) -> f32),
+}
+
+impl BlurTransitionEasing {
+    fn ease(&self, t: f32) -> f32 {
+        match self {
+            BlurTransitionEasing::Linear => t,
+            BlurTransitionEasing::QuadraticIn => t * t,
+            BlurTransitionEasing::QuadraticOut => 1.0 - (1.0 - t).powi(2),
+            BlurTransitionEasing::QuadraticInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            BlurTransitionEasing::CubicIn => t * t * t,
+            BlurTransitionEasing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            BlurTransitionEasing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            BlurTransitionEasing::Custom(ease) => ease(t),
+        }
+    }
+}
+
+/// The clock that a [`BlurTransition`] advances with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlurTransitionClock {
+    /// Real time, which keeps running while virtual time is paused, so that the blur of a pause
+    /// menu still fades in.
+    #[default]
+    Real,
+    /// Virtual time, which pauses and scales along with the rest of the game.
+    Virtual,
+}
+
+/// Sent when a [`BlurTransition`] reaches the end of its duration.
+#[derive(Event, Debug, Clone)]
+pub struct BlurTransitionFinished {
+    /// The entity the transition is on.
+    pub entity: Entity,
+}
+
+fn tick_blur_transitions(
+    real_time: Res<Time<Real>>,
+    virtual_time: Res<Time<Virtual>>,
+    mut transitions: Query<(Entity, &mut BlurTransition)>,
+    mut finished_events: EventWriter<BlurTransitionFinished>,
+) {
+    for (entity, mut transition) in &mut transitions {
+        if transition.finished {
+            continue;
+        }
+
+        let delta = match transition.clock {
+            BlurTransitionClock::Real => real_time.delta(),
+            BlurTransitionClock::Virtual => virtual_time.delta(),
+        };
+        transition.elapsed += delta;
+        if transition.elapsed >= transition.duration {
+            transition.elapsed = transition.duration;
+            transition.finished = true;
+            finished_events.send(BlurTransitionFinished { entity });
+        }
+    }
+}
+
+fn blur_fullscreen_transitions(mut blur_region_cameras: Query<(&Camera, &mut BlurRegionsCamera, &BlurTransition)>) {
+    for (camera, mut blur_regions, transition) in &mut blur_region_cameras {
+        let Some(size) = camera.physical_target_size() else {
+            continue;
+        };
+
+        if let Some(settings) = fullscreen_transition_settings(transition, blur_regions.settings()) {
+            blur_regions.blur_with(Rect::from_corners(Vec2::ZERO, size.as_vec2()), settings);
+        }
+    }
+}
+
+/// The settings of the fullscreen blur of a camera with a transition, or `None` when the blur
+/// wouldn't change the image, such as once a transition is reversed back to no blur, so that the
+/// camera can skip its blur passes.
+fn fullscreen_transition_settings(
+    transition: &BlurTransition,
+    camera_settings: BlurRegionSettings,
+) -> Option<BlurRegionSettings> {
+    Some(transition.apply(camera_settings)).filter(|settings| !settings.is_noop())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blurred() -> BlurTransitionState {
+        BlurTransitionState {
+            circle_of_confusion: 100.0,
+            tint: Color::NONE,
+        }
+    }
+
+    #[test]
+    fn state_follows_the_easing() {
+        let mut transition = BlurTransition::new(Duration::from_secs(2), BlurTransitionState::default(), blurred())
+            .with_easing(BlurTransitionEasing::Linear);
+        assert_eq!(transition.state().circle_of_confusion, 0.0);
+
+        transition.elapsed = Duration::from_millis(500);
+        assert_eq!(transition.state().circle_of_confusion, 25.0);
+
+        transition.easing = BlurTransitionEasing::QuadraticIn;
+        assert_eq!(transition.state().circle_of_confusion, 6.25);
+
+        transition.elapsed = Duration::from_secs(3);
+        assert_eq!(transition.state().circle_of_confusion, 100.0);
+    }
+
+    #[test]
+    fn zero_duration_jumps_to_the_end() {
+        let transition = BlurTransition::new(Duration::ZERO, BlurTransitionState::default(), blurred());
+        assert_eq!(transition.state(), blurred());
+    }
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        for easing in [
+            BlurTransitionEasing::Linear,
+            BlurTransitionEasing::QuadraticIn,
+            BlurTransitionEasing::QuadraticOut,
+            BlurTransitionEasing::QuadraticInOut,
+            BlurTransitionEasing::CubicIn,
+            BlurTransitionEasing::CubicOut,
+            BlurTransitionEasing::CubicInOut,
+        ] {
+            assert_eq!(easing.ease(0.0), 0.0, "{easing:?}");
+            assert_eq!(easing.ease(1.0), 1.0, "{easing:?}");
+        }
+        assert_eq!(BlurTransitionEasing::QuadraticInOut.ease(0.5), 0.5);
+        assert_eq!(BlurTransitionEasing::CubicInOut.ease(0.5), 0.5);
+        assert_eq!(BlurTransitionEasing::Custom(|t| t * 0.5).ease(1.0), 0.5);
+    }
+
+    #[test]
+    fn reverse_continues_from_the_current_state() {
+        let mut transition = BlurTransition::new(Duration::from_secs(1), BlurTransitionState::default(), blurred())
+            .with_easing(BlurTransitionEasing::Linear);
+        transition.elapsed = Duration::from_millis(250);
+        let state = transition.state();
+
+        transition.reverse();
+        assert_eq!(transition.from, blurred());
+        assert_eq!(transition.to, BlurTransitionState::default());
+        assert_eq!(transition.elapsed, Duration::from_millis(750));
+        assert_eq!(transition.state(), state);
+    }
+
+    #[test]
+    fn reverse_restarts_a_finished_transition() {
+        let mut transition = BlurTransition::new(Duration::from_secs(1), BlurTransitionState::default(), blurred());
+        transition.elapsed = transition.duration;
+        transition.finished = true;
+
+        transition.reverse();
+        assert!(!transition.is_finished());
+        assert_eq!(transition.elapsed, Duration::ZERO);
+        assert_eq!(transition.state(), blurred());
+    }
+
+    #[test]
+    fn fullscreen_transition_skips_noop_blurs() {
+        let mut transition = BlurTransition::new(Duration::from_secs(1), BlurTransitionState::default(), blurred());
+        assert!(fullscreen_transition_settings(&transition, default()).is_none());
+
+        transition.elapsed = Duration::from_millis(500);
+        assert!(fullscreen_transition_settings(&transition, default()).is_some());
+
+        // Reversed back to where it started.
+        transition.reverse();
+        transition.elapsed = transition.duration;
+        assert!(fullscreen_transition_settings(&transition, default()).is_none());
+    }
+
+    #[test]
+    fn fullscreen_transition_keeps_tints_and_color_adjustments() {
+        let tinted = BlurTransitionState {
+            circle_of_confusion: 0.0,
+            tint: Color::srgba(0.0, 0.0, 0.0, 0.5),
+        };
+        let transition = BlurTransition::new(Duration::ZERO, BlurTransitionState::default(), tinted);
+        assert!(fullscreen_transition_settings(&transition, default()).is_some());
+
+        let transition = BlurTransition::new(Duration::ZERO, blurred(), BlurTransitionState::default());
+        let greyscale = BlurRegionSettings {
+            saturation: 0.0,
+            ..default()
+        };
+        assert!(fullscreen_transition_settings(&transition, greyscale).is_some());
+    }
+}
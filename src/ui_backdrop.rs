@@ -0,0 +1,139 @@
+use bevy::core_pipeline::core_2d::graph::Core2d;
+use bevy::core_pipeline::core_2d::graph::Node2d;
+use bevy::core_pipeline::core_3d::graph::Core3d;
+use bevy::core_pipeline::core_3d::graph::Node3d;
+use bevy::ecs::query::QueryItem;
+use bevy::ecs::system::lifetimeless::Read;
+use bevy::prelude::*;
+use bevy::render::camera::ExtractedCamera;
+use bevy::render::render_graph::NodeRunError;
+use bevy::render::render_graph::RenderGraphApp;
+use bevy::render::render_graph::RenderGraphContext;
+use bevy::render::render_graph::RenderLabel;
+use bevy::render::render_graph::ViewNode;
+use bevy::render::render_graph::ViewNodeRunner;
+use bevy::render::render_phase::SortedRenderPhase;
+use bevy::render::render_phase::ViewSortedRenderPhases;
+use bevy::render::render_resource::RenderPassDescriptor;
+use bevy::render::renderer::RenderContext;
+use bevy::render::view::ViewTarget;
+use bevy::render::Render;
+use bevy::render::RenderApp;
+use bevy::render::RenderSet;
+use bevy::ui::DefaultCameraView;
+use bevy::ui::TransparentUi;
+
+use crate::BlurRegionsCamera;
+use crate::BlurRegionsLabel;
+
+/// Draws the Bevy UI from the lowest blurred node up after the blur regions node, for
+/// [`BlurRegionsGraphPlacement::UiBackdrop`](crate::BlurRegionsGraphPlacement::UiBackdrop).
+///
+/// The UI items above the split are moved out of the UI phase of the camera into a phase of their
+/// own, so that Bevy UI prepares and batches them as usual, but only draws the ones below the split
+/// in its own pass.
+///
+/// Must be added after [`BlurRegionsShaderPlugin`](crate::shader::BlurRegionsShaderPlugin), as
+/// its render graph node runs after the blur regions node.
+pub struct BlurRegionsUiBackdropPlugin;
+
+impl Plugin for BlurRegionsUiBackdropPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            // After the UI phases are sorted, and before Bevy UI batches them.
+            .add_systems(Render, split_ui_phases.in_set(RenderSet::Prepare));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        // The blur regions node is only added to the render graphs when its plugin finishes.
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<BlurredUiNode>>(Core3d, BlurredUiLabel)
+            .add_render_graph_edges(Core3d, (BlurRegionsLabel, BlurredUiLabel, Node3d::Upscaling))
+            .add_render_graph_node::<ViewNodeRunner<BlurredUiNode>>(Core2d, BlurredUiLabel)
+            .add_render_graph_edges(Core2d, (BlurRegionsLabel, BlurredUiLabel, Node2d::Upscaling));
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct BlurredUiLabel;
+
+/// The key of the phase holding the UI items drawn after the blur for a camera.
+#[derive(Component)]
+pub struct BlurredUiPhase(Entity);
+
+fn split_ui_phases(
+    mut commands: Commands,
+    mut phases: ResMut<ViewSortedRenderPhases<TransparentUi>>,
+    views: Query<(Entity, &BlurRegionsCamera)>,
+) {
+    for (entity, blur_regions) in &views {
+        let Some(ui_layer) = blur_regions.ui_layer() else {
+            continue;
+        };
+
+        let Some(phase) = phases.get_mut(&entity) else {
+            continue;
+        };
+
+        // The items are sorted by stack index, so everything from the first item at or above the
+        // lowest blurred node is drawn after the blur. Bevy UI removes the phase again next frame,
+        // as its key isn't a camera.
+        let split = phase.items.partition_point(|item| item.sort_key.0 .0 < ui_layer as f32);
+        let items = phase.items.split_off(split);
+        let phase_entity = commands.spawn_empty().id();
+        phases.insert(phase_entity, SortedRenderPhase { items });
+        commands.entity(entity).insert(BlurredUiPhase(phase_entity));
+    }
+}
+
+#[derive(Default)]
+pub struct BlurredUiNode;
+
+impl ViewNode for BlurredUiNode {
+    type ViewQuery = (
+        Read<ViewTarget>,
+        Read<ExtractedCamera>,
+        Read<BlurredUiPhase>,
+        Option<Read<DefaultCameraView>>,
+    );
+
+    fn run(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, camera, blurred_ui_phase, default_camera_view): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let Some(phase) = world.resource::<ViewSortedRenderPhases<TransparentUi>>().get(&blurred_ui_phase.0) else {
+            return Ok(());
+        };
+        if phase.items.is_empty() {
+            return Ok(());
+        }
+
+        // Like the Bevy UI pass, the items are drawn with the UI view of the camera if it has one.
+        let view_entity = default_camera_view.map_or(graph.view_entity(), |view| view.0);
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("blurred ui pass"),
+            color_attachments: &[Some(view_target.get_unsampled_color_attachment())],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        if let Some(viewport) = camera.viewport.as_ref() {
+            render_pass.set_camera_viewport(viewport);
+        }
+        phase.render(&mut render_pass, world, view_entity);
+
+        Ok(())
+    }
+}
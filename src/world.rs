@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+
+use crate::BlurRegionSettings;
+use crate::BlurRegionsCamera;
+use crate::BlurRegionsSystems;
+use crate::BlurTransition;
+
+pub struct BlurRegionsWorldPlugin;
+
+impl Plugin for BlurRegionsWorldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Last, compute_world_blur_regions.in_set(BlurRegionsSystems::Compute));
+    }
+}
+
+/// A blur region attached to an entity in the world, such as a floating nameplate or an
+/// in-world screen.
+///
+/// The blur region is a rectangle of the given size in the local XY plane of the entity, centered
+/// on its [`GlobalTransform`]. Each frame, the corners of the rectangle are projected onto the
+/// screen and a rectangle matching their size and rotation is blurred. Blur regions that are
+/// off-screen or behind the camera are skipped.
+///
+/// Add a [`BlurRegionSettings`] component to the same entity to override the settings of the
+/// camera, or a [`BlurTransition`] to animate the blur.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct WorldBlurRegion {
+    /// The size of the blur region.
+    /// In world units, before the scale of the entity is applied.
+    pub size: Vec2,
+    /// The camera that renders this blur region, or `None` for every camera with a
+    /// [`BlurRegionsCamera`].
+    pub camera: Option<Entity>,
+}
+
+impl WorldBlurRegion {
+    pub fn new(size: Vec2) -> Self {
+        WorldBlurRegion { size, camera: None }
+    }
+
+    pub fn with_camera(mut self, camera: Entity) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+}
+
+/// Add this component to a `Sprite` or a `Mesh2dHandle` to create a blur region covering its
+/// bounds, so that in-world 2D panels stay in place as the camera pans and zooms.
+///
+/// The bounds are taken from the [`Aabb`] of the entity, which Bevy computes for sprites and 2D
+/// meshes. Blur regions that are off-screen are skipped.
+///
+/// Add a [`BlurRegionSettings`] component to the same entity to override the settings of the
+/// camera, or a [`BlurTransition`] to animate the blur.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct BoundsBlurRegion {
+    /// The camera that renders this blur region, or `None` for every camera with a
+    /// [`BlurRegionsCamera`].
+    pub camera: Option<Entity>,
+}
+
+impl BoundsBlurRegion {
+    pub fn with_camera(mut self, camera: Entity) -> Self {
+        self.camera = Some(camera);
+        self
+    }
+}
+
+/// A rect in the local XY plane of an entity, projected onto the viewport of a camera.
+/// In logical pixels, relative to the viewport.
+struct ProjectedRect {
+    center: Vec2,
+    half_size: Vec2,
+    rotation: Rot2,
+    bounds: Rect,
+}
+
+/// Projects the corners of a rect in the local XY plane of an entity onto the viewport of the
+/// camera, or returns `None` if any corner is behind the camera.
+fn project_rect(
+    local: Rect,
+    transform: &GlobalTransform,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<ProjectedRect> {
+    let [a, b, c, d] = [
+        local.min,
+        Vec2::new(local.max.x, local.min.y),
+        local.max,
+        Vec2::new(local.min.x, local.max.y),
+    ]
+    .map(|corner| camera.world_to_viewport(camera_transform, transform.transform_point(corner.extend(0.0))));
+    let (a, b, c, d) = (a?, b?, c?, d?);
+
+    // Perspective can turn the rect into any quadrilateral, which is approximated by a rect with the
+    // average width and height of the quadrilateral. The viewport is y down, so the angle of the
+    // x axis is negated to get a counterclockwise rotation.
+    let x_axis = (b - a) + (c - d);
+    Some(ProjectedRect {
+        center: (a + b + c + d) * 0.25,
+        half_size: Vec2::new(
+            (a.distance(b) + d.distance(c)) * 0.25,
+            (a.distance(d) + b.distance(c)) * 0.25,
+        ),
+        rotation: Rot2::radians(-x_axis.y.atan2(x_axis.x)),
+        bounds: Rect::from_corners(a, b).union_point(c).union_point(d),
+    })
+}
+
+#[allow(clippy::type_complexity)]
+fn compute_world_blur_regions(
+    world_regions: Query<(
+        &WorldBlurRegion,
+        &GlobalTransform,
+        Option<&InheritedVisibility>,
+        Option<&BlurRegionSettings>,
+        Option<&BlurTransition>,
+    )>,
+    bounds_regions: Query<(
+        &BoundsBlurRegion,
+        &Aabb,
+        &GlobalTransform,
+        Option<&InheritedVisibility>,
+        Option<&BlurRegionSettings>,
+        Option<&BlurTransition>,
+    )>,
+    mut blur_regions_cameras: Query<(Entity, &Camera, &GlobalTransform, &mut BlurRegionsCamera)>,
+) {
+    for (camera_entity, camera, camera_transform, mut blur_regions) in &mut blur_regions_cameras {
+        if !camera.is_active {
+            continue;
+        }
+
+        let (Some(viewport), Some(scale_factor)) = (camera.logical_viewport_rect(), camera.target_scaling_factor())
+        else {
+            continue;
+        };
+
+        let world_regions = world_regions.iter().map(|(region, transform, visibility, settings, transition)| {
+            let local = Rect::from_center_size(Vec2::ZERO, region.size);
+            (region.camera, local, transform, visibility, settings, transition)
+        });
+        let bounds_regions =
+            bounds_regions.iter().map(|(region, aabb, transform, visibility, settings, transition)| {
+                let local = Rect::from_center_half_size(aabb.center.truncate(), aabb.half_extents.truncate());
+                (region.camera, local, transform, visibility, settings, transition)
+            });
+
+        for (target, local, transform, visibility, settings, transition) in world_regions.chain(bounds_regions) {
+            if target.is_some_and(|target| target != camera_entity) {
+                continue;
+            }
+
+            if visibility.is_some_and(|visibility| !visibility.get()) {
+                continue;
+            }
+
+            let Some(projected) = project_rect(local, transform, camera, camera_transform) else {
+                continue;
+            };
+
+            // Viewport coordinates are relative to the viewport, while blur regions are relative to
+            // the render target.
+            let bounds = Rect {
+                min: projected.bounds.min + viewport.min,
+                max: projected.bounds.max + viewport.min,
+            };
+            if bounds.intersect(viewport).is_empty() {
+                continue;
+            }
+
+            let mut settings = settings.copied().unwrap_or_else(|| blur_regions.settings());
+            if let Some(transition) = transition {
+                settings = transition.apply(settings);
+            }
+            let rect = Rect::from_center_half_size(
+                (projected.center + viewport.min) * scale_factor,
+                projected.half_size * scale_factor,
+            );
+            blur_regions.rotated_blur_with(rect, Vec4::ZERO, projected.rotation, settings);
+        }
+    }
+}